@@ -0,0 +1,113 @@
+// SPDX-License-Identifier: MIT
+// SPDX-FileCopyrightText: Copyright (c) 2025 Asymptotic Inc.
+// SPDX-FileCopyrightText: Copyright (c) 2025 Sanchayan Maity
+
+//! Wrappers returning [`anyhow::Result`](::anyhow::Result), with the `anyhow` feature.
+//!
+//! These call the [`RTKit`] methods of the same name, adding context describing the request to
+//! any error, which remains available as the [`RtkitError`](crate::RtkitError) source of the
+//! returned [`anyhow::Error`](::anyhow::Error).
+//!
+//! ```no_run
+//! use rtkit_rs::RTKit;
+//!
+//! fn go_realtime() -> anyhow::Result<()> {
+//!     let rtkit = rtkit_rs::anyhow::new()?;
+//!
+//!     rtkit_rs::anyhow::apply_max_rttime_limit(&rtkit)?;
+//!     rtkit_rs::anyhow::make_thread_realtime(&rtkit, RTKit::current_thread_id(), 10)?;
+//!
+//!     Ok(())
+//! }
+//! ```
+
+use ::anyhow::{Context, Result};
+
+use crate::{NiceLevel, RTKit, RealtimePriority, ThreadId};
+
+/// Wraps [`RTKit::new()`].
+pub fn new() -> Result<RTKit> {
+    RTKit::new().context("failed to connect to rtkit")
+}
+
+/// Wraps [`RTKit::apply_max_rttime_limit()`].
+pub fn apply_max_rttime_limit(rtkit: &RTKit) -> Result<u64> {
+    rtkit
+        .apply_max_rttime_limit()
+        .context("failed to apply the maximum RLIMIT_RTTIME")
+}
+
+/// Wraps [`RTKit::make_thread_realtime()`].
+pub fn make_thread_realtime(
+    rtkit: &RTKit,
+    thread_id: impl Into<ThreadId>,
+    priority: impl Into<RealtimePriority>,
+) -> Result<()> {
+    let thread_id = thread_id.into();
+    let priority = priority.into();
+
+    rtkit
+        .make_thread_realtime(thread_id, priority)
+        .with_context(|| {
+            format!(
+                "failed to make thread {thread_id} real-time with priority {}",
+                priority.get()
+            )
+        })
+}
+
+/// Wraps [`RTKit::make_thread_high_priority()`].
+pub fn make_thread_high_priority(
+    rtkit: &RTKit,
+    thread_id: impl Into<ThreadId>,
+    priority: impl Into<NiceLevel>,
+) -> Result<()> {
+    let thread_id = thread_id.into();
+    let priority = priority.into();
+
+    rtkit
+        .make_thread_high_priority(thread_id, priority)
+        .with_context(|| {
+            format!(
+                "failed to set the nice level of thread {thread_id} to {}",
+                priority.get()
+            )
+        })
+}
+
+/// Wraps [`RTKit::go_realtime()`].
+pub fn go_realtime(rtkit: &RTKit, desired_priority: u32) -> Result<()> {
+    rtkit
+        .go_realtime(desired_priority)
+        .with_context(|| format!("failed to go real-time with priority {desired_priority}"))
+}
+
+/// Wraps [`RTKit::reset_thread()`].
+pub fn reset_thread(thread_id: impl Into<ThreadId>) -> Result<()> {
+    let thread_id = thread_id.into();
+
+    RTKit::reset_thread(thread_id)
+        .with_context(|| format!("failed to reset the scheduling of thread {thread_id}"))
+}
+
+#[cfg(all(test, feature = "test-util", target_os = "linux"))]
+mod tests {
+    use super::*;
+    use crate::{MockRtkit, RtkitError};
+
+    #[test]
+    fn test_anyhow_wrappers() {
+        let mock = MockRtkit::new().unwrap();
+        let rtkit = RTKit::from_connection(mock.connection()).unwrap();
+
+        make_thread_realtime(&rtkit, 1234, 10).unwrap();
+        make_thread_high_priority(&rtkit, 1234, -5).unwrap();
+
+        let error = make_thread_realtime(&rtkit, 1234, 99).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "failed to make thread 1234 real-time with priority 99"
+        );
+        assert!(error.downcast_ref::<RtkitError>().is_some());
+    }
+}
@@ -0,0 +1,454 @@
+// SPDX-License-Identifier: MIT
+// SPDX-FileCopyrightText: Copyright (c) 2025 Asymptotic Inc.
+// SPDX-FileCopyrightText: Copyright (c) 2025 Sanchayan Maity
+
+//! An asynchronous API, built on top of the non-blocking [`zbus::Connection`].
+//!
+//! This mirrors the blocking [`RTKit`](crate::RTKit) API, and is meant for use from within an
+//! async runtime, where making blocking D-Bus calls would stall the executor.
+//!
+//! ```no_run
+//! use rtkit_rs::{AsyncRTKit, RTKit, RtkitError};
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), RtkitError> {
+//!     let rtkit = AsyncRTKit::new().await?;
+//!     let max = rtkit.max_realtime_priority().await?;
+//!
+//!     rtkit
+//!         .make_thread_realtime(RTKit::current_thread_id(), max as u32)
+//!         .await
+//! }
+//! ```
+//!
+//! # Cancellation
+//!
+//! All the methods of [`AsyncRTKit`] are cancellation-safe: dropping one of their futures before
+//! it completes, e.g. in a `select!` or when a timeout elapses, stops waiting for the reply, and
+//! leaves the connection usable for further calls. Any reply that arrives later is discarded.
+//!
+//! Note that a D-Bus call cannot be withdrawn once it has been sent, so the daemon may still act
+//! on a request whose future was dropped. For example, a thread may be made real-time after all,
+//! once PolicyKit grants the request.
+
+use futures_lite::{Stream, StreamExt};
+use zbus::export::serde::ser::Serialize;
+use zbus::zvariant::{DynamicType, Value};
+use zbus::{Connection, MatchRule, Message, MessageStream};
+
+use crate::{
+    BusKind, NiceLevel, ProcessId, RTKIT_OBJECT_PATH, RTKIT_SERVICE_NAME, RealtimePriority,
+    RtkitError, RtkitProperties, ThreadId, availability, error, proxy,
+};
+
+/// The asynchronous counterpart of [`RTKit`](crate::RTKit).
+pub struct AsyncRTKit {
+    connection: Connection,
+}
+
+impl AsyncRTKit {
+    /// Create an instance of the `AsyncRTKit` structure. This makes a connection to the system
+    /// D-Bus daemon, and ensures that the `rtkit` daemon is available.
+    ///
+    /// Returns an `AsyncRTKit` structure if the connection succeeds and the daemon is available,
+    /// or an error otherwise.
+    pub async fn new() -> Result<AsyncRTKit, RtkitError> {
+        if !cfg!(target_os = "linux") {
+            return Err(RtkitError::Unsupported);
+        }
+
+        Self::from_connection(
+            Connection::system()
+                .await
+                .map_err(|err| error::connection_failed(BusKind::System, err))?,
+        )
+        .await
+    }
+
+    /// Create an instance of the `AsyncRTKit` structure using an existing D-Bus `connection`,
+    /// which should usually be to the system bus. This ensures that the `rtkit` daemon is
+    /// available.
+    pub async fn from_connection(connection: Connection) -> Result<AsyncRTKit, RtkitError> {
+        if !cfg!(target_os = "linux") {
+            return Err(RtkitError::Unsupported);
+        }
+
+        if !availability::is_rtkit_available(&connection, RTKIT_SERVICE_NAME, RTKIT_OBJECT_PATH)
+            .await?
+        {
+            return Err(RtkitError::DaemonUnavailable);
+        }
+
+        Ok(AsyncRTKit { connection })
+    }
+
+    /// Returns whether the `rtkit` daemon is available on the system bus, without creating an
+    /// `AsyncRTKit` instance. See [`RTKit::is_available()`](crate::RTKit::is_available), which
+    /// this gives the same result as.
+    pub async fn is_available() -> Result<bool, RtkitError> {
+        if !cfg!(target_os = "linux") {
+            return Ok(false);
+        }
+
+        let connection = Connection::system()
+            .await
+            .map_err(|err| error::connection_failed(BusKind::System, err))?;
+
+        Ok(
+            availability::is_rtkit_available(&connection, RTKIT_SERVICE_NAME, RTKIT_OBJECT_PATH)
+                .await?,
+        )
+    }
+
+    /// Asks the system bus to start the `rtkit` daemon via D-Bus activation. See
+    /// [`RTKit::activate()`](crate::RTKit::activate), which this gives the same result as.
+    pub async fn activate() -> Result<(), RtkitError> {
+        if !cfg!(target_os = "linux") {
+            return Err(RtkitError::Unsupported);
+        }
+
+        let connection = Connection::system()
+            .await
+            .map_err(|err| error::connection_failed(BusKind::System, err))?;
+
+        if !availability::activate(&connection, RTKIT_SERVICE_NAME).await? {
+            return Err(RtkitError::DaemonUnavailable);
+        }
+
+        Ok(())
+    }
+
+    async fn call<B>(&self, request: &proxy::Request<'_, B>) -> Result<Message, RtkitError>
+    where
+        B: Serialize + DynamicType + std::fmt::Debug,
+    {
+        let &proxy::Request {
+            interface,
+            method,
+            ref body,
+        } = request;
+
+        #[cfg(feature = "tracing")]
+        let span = tracing::debug_span!("rtkit_call", interface, method, args = ?body);
+
+        let future = self.connection.call_method(
+            Some(RTKIT_SERVICE_NAME),
+            RTKIT_OBJECT_PATH,
+            Some(interface),
+            method,
+            body,
+        );
+
+        #[cfg(feature = "tracing")]
+        let future = tracing::Instrument::instrument(future, span.clone());
+
+        let result = future.await.map_err(RtkitError::from);
+
+        #[cfg(feature = "tracing")]
+        span.in_scope(|| crate::call::trace_result(&result));
+
+        result
+    }
+
+    async fn property<T>(&self, name: &'static str) -> Result<T, RtkitError>
+    where
+        T: for<'a> TryFrom<&'a Value<'a>>,
+    {
+        let message = self
+            .call(&proxy::get_property(name))
+            .await
+            .map_err(|err| proxy::property_error(name, err))?;
+
+        proxy::parse_property(name, &message)
+    }
+
+    /// Returns a stream that yields `true` when the `rtkit` daemon appears on the bus, and `false`
+    /// when it disappears, based on the bus' `NameOwnerChanged` signal.
+    ///
+    /// A daemon that has restarted knows nothing of the threads that were previously promoted,
+    /// and those threads will have been demoted when the previous instance exited, so any
+    /// promotions should be requested again when this yields `true`.
+    pub async fn watch_availability(&self) -> Result<impl Stream<Item = bool>, RtkitError> {
+        let rule = MatchRule::builder()
+            .msg_type(zbus::message::Type::Signal)
+            .sender("org.freedesktop.DBus")?
+            .path("/org/freedesktop/DBus")?
+            .interface("org.freedesktop.DBus")?
+            .member("NameOwnerChanged")?
+            .add_arg(RTKIT_SERVICE_NAME)?
+            .build();
+
+        let stream = MessageStream::for_match_rule(rule, &self.connection, None).await?;
+
+        // The arguments are the name, and its old and new owners (empty if there is none)
+        Ok(stream.filter_map(|message| {
+            let message = message.ok()?;
+            let (_, _, new_owner): (String, String, String) = message.body().deserialize().ok()?;
+
+            Some(!new_owner.is_empty())
+        }))
+    }
+
+    /// Returns the maximum permitted real-time priority value.
+    pub async fn max_realtime_priority(&self) -> Result<i32, RtkitError> {
+        self.property("MaxRealtimePriority").await
+    }
+
+    /// Returns the minimum permitted nice level value.
+    pub async fn min_nice_level(&self) -> Result<i32, RtkitError> {
+        self.property("MinNiceLevel").await
+    }
+
+    /// Returns the maximum time (in microseconds) that may be set for `RLIMIT_RTTIME`. See
+    /// [`RTKit::rttime_usec_max()`](crate::RTKit::rttime_usec_max) for details.
+    pub async fn rttime_usec_max(&self) -> Result<i64, RtkitError> {
+        self.property("RTTimeUSecMax").await
+    }
+
+    /// Returns all the daemon's properties, using a single D-Bus call.
+    pub async fn properties(&self) -> Result<RtkitProperties, RtkitError> {
+        let message = self.call(&proxy::get_all_properties()).await?;
+
+        proxy::parse_properties(&message)
+    }
+
+    /// Requests a nice level of `priority` for the specified thread id (this is a non-real-time
+    /// scheduling level).
+    pub async fn make_thread_high_priority(
+        &self,
+        thread_id: impl Into<ThreadId>,
+        priority: impl Into<NiceLevel>,
+    ) -> Result<(), RtkitError> {
+        let thread_id = thread_id.into();
+        let priority = priority.into();
+
+        self.call(&proxy::make_thread_high_priority(
+            thread_id.get(),
+            priority.get(),
+        ))
+        .await?;
+
+        Ok(())
+    }
+
+    /// Requests a nice level of `priority` for the specified thread id of a specified process id
+    /// (this is a non-real-time scheduling level).
+    pub async fn make_thread_high_priority_with_pid(
+        &self,
+        process_id: impl Into<ProcessId>,
+        thread_id: impl Into<ThreadId>,
+        priority: impl Into<NiceLevel>,
+    ) -> Result<(), RtkitError> {
+        let process_id = process_id.into();
+        let thread_id = thread_id.into();
+        let priority = priority.into();
+
+        self.call(&proxy::make_thread_high_priority_with_pid(
+            process_id.get(),
+            thread_id.get(),
+            priority.get(),
+        ))
+        .await?;
+
+        Ok(())
+    }
+
+    /// Requests a real-time priority of `priority` for the specified thread id.
+    pub async fn make_thread_realtime(
+        &self,
+        thread_id: impl Into<ThreadId>,
+        priority: impl Into<RealtimePriority>,
+    ) -> Result<(), RtkitError> {
+        let thread_id = thread_id.into();
+        let priority = priority.into();
+
+        self.call(&proxy::make_thread_realtime(
+            thread_id.get(),
+            priority.get(),
+        ))
+        .await?;
+
+        Ok(())
+    }
+
+    /// Requests a real-time priority of `priority` for the specified thread id of a specified
+    /// process id.
+    pub async fn make_thread_realtime_with_pid(
+        &self,
+        process_id: impl Into<ProcessId>,
+        thread_id: impl Into<ThreadId>,
+        priority: impl Into<RealtimePriority>,
+    ) -> Result<(), RtkitError> {
+        let process_id = process_id.into();
+        let thread_id = thread_id.into();
+        let priority = priority.into();
+
+        self.call(&proxy::make_thread_realtime_with_pid(
+            process_id.get(),
+            thread_id.get(),
+            priority.get(),
+        ))
+        .await?;
+
+        Ok(())
+    }
+
+    /// Resets all threads that `rtkit` has promoted (and is still tracking) back to non-real-time
+    /// scheduling.
+    pub async fn reset_known(&self) -> Result<(), RtkitError> {
+        self.call(&proxy::reset_known()).await?;
+
+        Ok(())
+    }
+
+    /// Resets every real-time and high priority thread on the system back to non-real-time
+    /// scheduling. See [`RTKit::reset_all()`](crate::RTKit::reset_all) for details.
+    pub async fn reset_all(&self) -> Result<(), RtkitError> {
+        self.call(&proxy::reset_all()).await?;
+
+        Ok(())
+    }
+
+    /// Asks the `rtkit` daemon to terminate. See [`RTKit::exit()`](crate::RTKit::exit) for
+    /// details.
+    pub async fn exit(&self) -> Result<(), RtkitError> {
+        self.call(&proxy::exit()).await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+    use crate::RTKit;
+
+    #[tokio::test]
+    async fn test_property() {
+        let rtkit = AsyncRTKit::new().await.unwrap();
+
+        // Test for default values
+        assert_eq!(rtkit.max_realtime_priority().await.unwrap(), 20);
+        assert_eq!(rtkit.min_nice_level().await.unwrap(), -15);
+        assert_eq!(rtkit.rttime_usec_max().await.unwrap(), 200000);
+    }
+
+    #[tokio::test]
+    async fn test_is_available() {
+        // Whether or not there is a system bus with rtkit on it, both front-ends should agree
+        assert_eq!(
+            AsyncRTKit::is_available().await.ok(),
+            RTKit::is_available().ok()
+        );
+        assert_eq!(
+            AsyncRTKit::activate().await.is_ok(),
+            RTKit::activate().is_ok()
+        );
+    }
+
+    #[cfg(feature = "test-util")]
+    #[tokio::test]
+    async fn test_cancellation() {
+        let mock = crate::MockRtkit::new().unwrap();
+        let rtkit = AsyncRTKit::from_connection(mock.connection().into_inner())
+            .await
+            .unwrap();
+
+        // Send a request, and then drop it before its reply is read
+        let future = rtkit.make_thread_realtime(1, 10);
+        assert!(futures_lite::future::poll_once(future).await.is_none());
+
+        // The connection is still usable, and replies are not mixed up
+        assert_eq!(rtkit.max_realtime_priority().await.unwrap(), 20);
+        rtkit.reset_known().await.unwrap();
+        assert!(matches!(
+            rtkit.make_thread_realtime(2, 21).await,
+            Err(RtkitError::PermissionDenied { .. })
+        ));
+        assert_eq!(rtkit.min_nice_level().await.unwrap(), -15);
+
+        // The dropped request was still acted on, as it had already been sent
+        assert_eq!(
+            mock.requests(),
+            vec![
+                crate::MockRequest::Realtime {
+                    process_id: None,
+                    thread_id: 1,
+                    priority: 10,
+                },
+                crate::MockRequest::ResetKnown,
+            ]
+        );
+    }
+
+    struct FakeBus;
+
+    #[zbus::interface(name = "org.freedesktop.DBus")]
+    impl FakeBus {
+        fn list_names(&self) -> Vec<String> {
+            vec![RTKIT_SERVICE_NAME.to_string()]
+        }
+    }
+
+    #[tokio::test]
+    async fn test_watch_availability() {
+        let (client, server) = std::os::unix::net::UnixStream::pair().unwrap();
+
+        let server = std::thread::spawn(move || {
+            zbus::blocking::connection::Builder::unix_stream(server)
+                .server(zbus::Guid::generate())
+                .unwrap()
+                .p2p()
+                .serve_at("/org/freedesktop/DBus", FakeBus)
+                .unwrap()
+                .build()
+                .unwrap()
+        });
+
+        let client = zbus::connection::Builder::unix_stream(client)
+            .p2p()
+            .build()
+            .await
+            .unwrap();
+        let server = server.join().unwrap();
+
+        let rtkit = AsyncRTKit::from_connection(client).await.unwrap();
+        let mut stream = std::pin::pin!(rtkit.watch_availability().await.unwrap());
+
+        // Signals from the bus are sent by `org.freedesktop.DBus`, which must be set explicitly on
+        // a peer-to-peer connection
+        let name_owner_changed = |old_owner: &str, new_owner: &str| {
+            let signal = Message::signal(
+                "/org/freedesktop/DBus",
+                "org.freedesktop.DBus",
+                "NameOwnerChanged",
+            )
+            .unwrap()
+            .sender("org.freedesktop.DBus")
+            .unwrap()
+            .build(&(RTKIT_SERVICE_NAME, old_owner, new_owner))
+            .unwrap();
+
+            server.send(&signal).unwrap()
+        };
+
+        // The daemon exits, and is restarted
+        name_owner_changed(":1.1", "");
+        assert_eq!(stream.next().await, Some(false));
+        name_owner_changed("", ":1.2");
+        assert_eq!(stream.next().await, Some(true));
+    }
+
+    #[tokio::test]
+    async fn test_make_thread_high_priority() {
+        let rtkit = AsyncRTKit::new().await.unwrap();
+
+        let thread_id = RTKit::current_thread_id();
+        assert!(
+            rtkit
+                .make_thread_high_priority(thread_id, -10)
+                .await
+                .is_ok()
+        );
+    }
+}
@@ -0,0 +1,106 @@
+// SPDX-License-Identifier: MIT
+// SPDX-FileCopyrightText: Copyright (c) 2025 Asymptotic Inc.
+// SPDX-FileCopyrightText: Copyright (c) 2025 Sanchayan Maity
+
+// Checks for the daemon being on the bus, and starting it via D-Bus activation. These are shared
+// by the blocking and async front-ends, the former blocking on them, so that both always give the
+// same answer.
+
+use zbus::Connection;
+
+use crate::proxy;
+
+const BUS_NAME: &str = "org.freedesktop.DBus";
+const BUS_PATH: &str = "/org/freedesktop/DBus";
+
+// Checks whether the service is on the bus using `ListNames`. Some bus policies deny that, in which
+// case we instead try reading one of the daemon's properties, treating success as proof that it is
+// available.
+pub(crate) async fn is_rtkit_available(
+    connection: &Connection,
+    service_name: &str,
+    object_path: &str,
+) -> zbus::Result<bool> {
+    let message = match connection
+        .call_method(Some(BUS_NAME), BUS_PATH, Some(BUS_NAME), "ListNames", &())
+        .await
+    {
+        Ok(message) => message,
+        Err(zbus::Error::MethodError(name, _, _))
+            if name.as_str() == "org.freedesktop.DBus.Error.AccessDenied" =>
+        {
+            return can_read_property(connection, service_name, object_path).await;
+        }
+        Err(e) => return Err(e),
+    };
+
+    let names: Vec<String> = message.body().deserialize()?;
+
+    Ok(names.iter().any(|name| name == service_name))
+}
+
+async fn can_read_property(
+    connection: &Connection,
+    service_name: &str,
+    object_path: &str,
+) -> zbus::Result<bool> {
+    let request = proxy::get_property("MaxRealtimePriority");
+
+    match connection
+        .call_method(
+            Some(service_name),
+            object_path,
+            Some(request.interface),
+            request.method,
+            &request.body,
+        )
+        .await
+    {
+        Ok(_) => Ok(true),
+        // Any error from the bus or the service means that we can't use it
+        Err(zbus::Error::MethodError(..)) => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+// Asks the bus to start the service via D-Bus activation, returning `false` if it is not
+// activatable
+pub(crate) async fn activate(connection: &Connection, service_name: &str) -> zbus::Result<bool> {
+    match connection
+        .call_method(
+            Some(BUS_NAME),
+            BUS_PATH,
+            Some(BUS_NAME),
+            "StartServiceByName",
+            &(service_name, 0u32),
+        )
+        .await
+    {
+        Ok(_) => Ok(true),
+        Err(zbus::Error::MethodError(name, _, _))
+            if name.as_str() == "org.freedesktop.DBus.Error.ServiceUnknown" =>
+        {
+            Ok(false)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+// Checks if the service is available, and if not and `auto_start` is set, asks the bus to start it
+// and checks again.
+pub(crate) async fn ensure_rtkit_available(
+    connection: &Connection,
+    service_name: &str,
+    object_path: &str,
+    auto_start: bool,
+) -> zbus::Result<bool> {
+    if is_rtkit_available(connection, service_name, object_path).await? {
+        return Ok(true);
+    }
+
+    if !auto_start || !activate(connection, service_name).await? {
+        return Ok(false);
+    }
+
+    is_rtkit_available(connection, service_name, object_path).await
+}
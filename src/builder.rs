@@ -0,0 +1,379 @@
+// SPDX-License-Identifier: MIT
+// SPDX-FileCopyrightText: Copyright (c) 2025 Asymptotic Inc.
+// SPDX-FileCopyrightText: Copyright (c) 2025 Sanchayan Maity
+
+use std::time::Duration;
+
+use zbus::AuthMechanism;
+use zbus::blocking::Connection;
+use zbus::blocking::connection::Builder;
+
+use crate::{RTKIT_OBJECT_PATH, RTKIT_SERVICE_NAME, RTKit, RtkitError, call, error};
+
+enum Bus {
+    System,
+    Session,
+    Address(String),
+}
+
+impl Bus {
+    fn kind(&self) -> BusKind {
+        match self {
+            Bus::System => BusKind::System,
+            Bus::Session => BusKind::Session,
+            Bus::Address(address) => BusKind::Address(address.clone()),
+        }
+    }
+}
+
+/// The bus that an [`RTKit`] is connected to, as returned by [`RTKit::bus_kind()`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BusKind {
+    /// The system bus.
+    System,
+    /// The session bus.
+    Session,
+    /// The bus at the given D-Bus address, as set with [`RTKitBuilder::address()`].
+    Address(String),
+    /// An existing connection, given to [`RTKit::from_connection()`], whose bus is not known.
+    Existing,
+}
+
+impl std::fmt::Display for BusKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BusKind::System => f.write_str("system bus"),
+            BusKind::Session => f.write_str("session bus"),
+            BusKind::Address(address) => write!(f, "bus at {address}"),
+            BusKind::Existing => f.write_str("existing connection"),
+        }
+    }
+}
+
+// The options that are retained by `RTKit` once built.
+#[derive(Clone, Debug)]
+pub(crate) struct Config {
+    pub(crate) service_name: String,
+    pub(crate) object_path: String,
+    pub(crate) cache_properties: bool,
+    pub(crate) timeout: Option<Duration>,
+    pub(crate) auto_start: bool,
+    pub(crate) retries: u32,
+    pub(crate) retry_backoff: Duration,
+    pub(crate) reset_known_on_drop: bool,
+    pub(crate) interactive_authorization: bool,
+    pub(crate) bus_kind: BusKind,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            service_name: RTKIT_SERVICE_NAME.to_string(),
+            object_path: RTKIT_OBJECT_PATH.to_string(),
+            cache_properties: false,
+            timeout: None,
+            auto_start: false,
+            retries: 0,
+            retry_backoff: Duration::from_millis(100),
+            reset_known_on_drop: false,
+            interactive_authorization: false,
+            bus_kind: BusKind::Existing,
+        }
+    }
+}
+
+/// A builder for [`RTKit`], allowing the bus and the names used to reach the `rtkit` daemon to be
+/// configured.
+///
+/// The defaults match [`RTKit::new()`], i.e. the system bus, with the standard
+/// `org.freedesktop.RealtimeKit1` service name and `/org/freedesktop/RealtimeKit1` object path.
+pub struct RTKitBuilder {
+    bus: Bus,
+    session_bus_fallback: bool,
+    auth_mechanism: Option<AuthMechanism>,
+    config: Config,
+}
+
+impl Default for RTKitBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RTKitBuilder {
+    /// Create a new builder with the default configuration.
+    pub fn new() -> RTKitBuilder {
+        RTKitBuilder {
+            bus: Bus::System,
+            session_bus_fallback: false,
+            auth_mechanism: None,
+            config: Config::default(),
+        }
+    }
+
+    /// Connect to the system bus (this is the default).
+    pub fn system_bus(mut self) -> Self {
+        self.bus = Bus::System;
+        self
+    }
+
+    /// Connect to the session bus, for example to talk to a mock `rtkit` implementation.
+    pub fn session_bus(mut self) -> Self {
+        self.bus = Bus::Session;
+        self
+    }
+
+    /// Connect to the bus at the given D-Bus `address`, e.g.
+    /// `unix:path=/run/dbus/system_bus_socket`.
+    ///
+    /// This is not needed just to use a system bus at a non-standard location, as the address in
+    /// the `DBUS_SYSTEM_BUS_ADDRESS` environment variable is used for the system bus if it is set.
+    pub fn address(mut self, address: &str) -> Self {
+        self.bus = Bus::Address(address.to_string());
+        self
+    }
+
+    /// If the `rtkit` service cannot be reached on the system bus, try the session bus instead
+    /// (disabled by default). This has no effect if [`session_bus()`](Self::session_bus) or
+    /// [`address()`](Self::address) is used.
+    ///
+    /// Some sandboxed environments, such as Flatpak, do not give direct access to the system bus,
+    /// but expose `rtkit` on the session bus via a proxy.
+    pub fn session_bus_fallback(mut self, fallback: bool) -> Self {
+        self.session_bus_fallback = fallback;
+        self
+    }
+
+    /// Set the mechanism used to authenticate with the bus. By default, `EXTERNAL` is used, which
+    /// passes the caller's credentials over the socket.
+    ///
+    /// Some container setups do not pass credentials through correctly, in which case
+    /// [`build()`](Self::build) fails with [`RtkitError::AuthFailed`], and a bus configured to
+    /// allow it may accept `ANONYMOUS` authentication instead. Note that the system bus normally
+    /// does not, and that `rtkit` needs to know the caller's credentials in any case.
+    pub fn auth_mechanism(mut self, auth_mechanism: AuthMechanism) -> Self {
+        self.auth_mechanism = Some(auth_mechanism);
+        self
+    }
+
+    /// Set the well-known bus name of the `rtkit` service.
+    pub fn service_name(mut self, service_name: &str) -> Self {
+        self.config.service_name = service_name.to_string();
+        self
+    }
+
+    /// Set the object path at which the `rtkit` service exports its interface.
+    pub fn object_path(mut self, object_path: &str) -> Self {
+        self.config.object_path = object_path.to_string();
+        self
+    }
+
+    /// Cache the daemon's properties after they are first read (disabled by default). See
+    /// [`RTKit::new_cached()`] for the trade-offs involved.
+    pub fn cache_properties(mut self, cache_properties: bool) -> Self {
+        self.config.cache_properties = cache_properties;
+        self
+    }
+
+    /// Set the maximum time to wait for a reply to each call made to the daemon (there is no
+    /// timeout by default). If this elapses, the call fails with [`RtkitError::Timeout`]. With
+    /// [`retries()`](Self::retries), this applies to each attempt separately.
+    ///
+    /// This is useful when `rtkit` is configured to ask PolicyKit for authorization, which may
+    /// block for a long time waiting on an authentication agent.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.config.timeout = Some(timeout);
+        self
+    }
+
+    /// If the `rtkit` service is not running, ask the bus to start it via D-Bus activation before
+    /// deciding that it is unavailable (disabled by default).
+    ///
+    /// On many systems, `rtkit` is only started on demand, and so is not present on the bus until
+    /// something tries to use it.
+    pub fn auto_start(mut self, auto_start: bool) -> Self {
+        self.config.auto_start = auto_start;
+        self
+    }
+
+    /// Retry connecting to the bus, and each call made to the daemon, up to `retries` times if it
+    /// fails with a transient error (there are no retries by default).
+    ///
+    /// The errors that are retried are transport failures while connecting, and the `NoReply`,
+    /// `ServiceUnknown`, `NameHasNoOwner` and `Disconnected` D-Bus errors, which may occur while
+    /// the daemon is restarting. Other errors, such as [`RtkitError::PermissionDenied`] or
+    /// [`RtkitError::Timeout`], are returned immediately, as are transport failures during a call,
+    /// since the connection cannot recover from those. All the daemon's methods are safe to
+    /// repeat, as requesting the same scheduling parameters twice has the same effect as once.
+    ///
+    /// Each attempt is subject to the [`timeout()`](Self::timeout) separately, so with both set, a
+    /// call may take up to `timeout * (retries + 1)`, plus the delays between attempts (see
+    /// [`retry_backoff()`](Self::retry_backoff)).
+    pub fn retries(mut self, retries: u32) -> Self {
+        self.config.retries = retries;
+        self
+    }
+
+    /// Set the delay before the first retry (100ms by default). The delay doubles for each
+    /// subsequent retry. See [`retries()`](Self::retries).
+    pub fn retry_backoff(mut self, backoff: Duration) -> Self {
+        self.config.retry_backoff = backoff;
+        self
+    }
+
+    /// Call [`RTKit::reset_known()`] when the built `RTKit` (and all clones of it) are dropped
+    /// (disabled by default), so that short-lived tools do not leave threads running with
+    /// real-time or high priority after they are done.
+    ///
+    /// As with an explicit call to `reset_known()`, this only resets threads that the daemon
+    /// promoted and is still tracking, and not those whose scheduling was changed through other
+    /// means. Since errors cannot be returned from `drop()`, any failure is ignored (and logged,
+    /// if the `tracing` feature is enabled). Call `reset_known()` explicitly to handle failures.
+    pub fn reset_known_on_drop(mut self, reset_known_on_drop: bool) -> Self {
+        self.config.reset_known_on_drop = reset_known_on_drop;
+        self
+    }
+
+    /// Allow the daemon to interactively ask the user for authorization, e.g. via a PolicyKit
+    /// authentication dialog, before replying to each call (disabled by default).
+    ///
+    /// By default, calls are made without the D-Bus `ALLOW_INTERACTIVE_AUTHORIZATION` flag, which
+    /// tells the daemon that no prompt should be shown. A request that would need one is then
+    /// refused immediately, rather than blocking while waiting for an authentication agent that
+    /// may not exist, which is what headless services and daemons started before any user session
+    /// want. Implementations that honour the flag reply with
+    /// `org.freedesktop.DBus.Error.InteractiveAuthorizationRequired`, which is returned as
+    /// [`RtkitError::NotAuthorized`].
+    ///
+    /// Enabling this is only useful for interactive applications. Consider also setting a
+    /// [`timeout()`](Self::timeout), as the user may take a while to respond.
+    pub fn interactive_authorization(mut self, interactive_authorization: bool) -> Self {
+        self.config.interactive_authorization = interactive_authorization;
+        self
+    }
+
+    /// Connect to the configured bus, and ensure the `rtkit` service is available on it.
+    ///
+    /// Returns an `RTKit` structure if the connection succeeds and the daemon is available, or an
+    /// error otherwise.
+    pub fn build(self) -> Result<RTKit, RtkitError> {
+        if !cfg!(target_os = "linux") {
+            return Err(RtkitError::Unsupported);
+        }
+
+        match self.bus {
+            Bus::System if self.session_bus_fallback => {
+                let system = self.connect(&Bus::System).and_then(|connection| {
+                    RTKit::from_parts(connection, self.config(&Bus::System))
+                });
+
+                // Either the system bus is not accessible, or rtkit is not on it
+                match system {
+                    Err(
+                        RtkitError::DaemonUnavailable
+                        | RtkitError::AuthFailed(_)
+                        | RtkitError::BusConnectionFailed { .. }
+                        | RtkitError::Dbus(_),
+                    ) => {
+                        RTKit::from_parts(self.connect(&Bus::Session)?, self.config(&Bus::Session))
+                    }
+                    result => result,
+                }
+            }
+            _ => RTKit::from_parts(self.connect(&self.bus)?, self.config(&self.bus)),
+        }
+    }
+
+    fn config(&self, bus: &Bus) -> Config {
+        Config {
+            bus_kind: bus.kind(),
+            ..self.config.clone()
+        }
+    }
+
+    fn connect(&self, bus: &Bus) -> Result<Connection, RtkitError> {
+        call::retry(self.config.retries, self.config.retry_backoff, || {
+            self.try_connect(bus)
+                .map_err(|err| error::connection_failed(bus.kind(), err))
+        })
+    }
+
+    fn try_connect(&self, bus: &Bus) -> zbus::Result<Connection> {
+        let mut builder = match bus {
+            Bus::System => Builder::system()?,
+            Bus::Session => Builder::session()?,
+            Bus::Address(address) => Builder::address(address.as_str())?,
+        };
+
+        if let Some(auth_mechanism) = self.auth_mechanism {
+            builder = builder.auth_mechanism(auth_mechanism);
+        }
+
+        builder.build()
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_address() {
+        // The given address is used, rather than the system bus
+        let result = RTKitBuilder::new()
+            .address("unix:path=/nonexistent/rtkit-rs-test")
+            .build();
+        assert!(matches!(
+            result,
+            Err(RtkitError::BusConnectionFailed {
+                bus: BusKind::Address(_),
+                source: zbus::Error::InputOutput(e),
+            }) if e.kind() == std::io::ErrorKind::NotFound
+        ));
+
+        assert_eq!(
+            Bus::Address("unix:path=/tmp/bus".to_string()).kind(),
+            BusKind::Address("unix:path=/tmp/bus".to_string())
+        );
+
+        assert!(matches!(
+            RTKitBuilder::new().address("not an address").build(),
+            Err(RtkitError::BusConnectionFailed { .. })
+        ));
+    }
+
+    #[test]
+    fn test_auth_failed() {
+        use std::io::{BufRead, BufReader, Write};
+
+        let dir = std::env::temp_dir().join(format!("rtkit-rs-auth-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("bus");
+        let _ = std::fs::remove_file(&path);
+        let listener = std::os::unix::net::UnixListener::bind(&path).unwrap();
+
+        // A bus that rejects every authentication attempt
+        let server = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut writer = stream;
+            let mut line = Vec::new();
+
+            while reader.read_until(b'\n', &mut line).is_ok_and(|n| n > 0) {
+                if writer.write_all(b"REJECTED EXTERNAL\r\n").is_err() {
+                    break;
+                }
+                line.clear();
+            }
+        });
+
+        let result = RTKitBuilder::new()
+            .address(&format!("unix:path={}", path.display()))
+            .auth_mechanism(AuthMechanism::Anonymous)
+            .build();
+        assert!(matches!(result, Err(RtkitError::AuthFailed(_))));
+
+        server.join().unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,83 @@
+// SPDX-License-Identifier: MIT
+// SPDX-FileCopyrightText: Copyright (c) 2025 Asymptotic Inc.
+// SPDX-FileCopyrightText: Copyright (c) 2025 Sanchayan Maity
+
+use std::sync::OnceLock;
+
+use crate::{RtkitError, RtkitProperties};
+
+// Caches the daemon's properties, which are fixed for the lifetime of the daemon.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct PropertyCache {
+    pub(crate) max_realtime_priority: OnceLock<i32>,
+    pub(crate) min_nice_level: OnceLock<i32>,
+    pub(crate) rttime_usec_max: OnceLock<i64>,
+}
+
+impl PropertyCache {
+    // Returns a cache holding the given values
+    pub(crate) fn from_properties(properties: &RtkitProperties) -> PropertyCache {
+        PropertyCache {
+            max_realtime_priority: OnceLock::from(properties.max_realtime_priority),
+            min_nice_level: OnceLock::from(properties.min_nice_level),
+            rttime_usec_max: OnceLock::from(properties.rttime_usec_max),
+        }
+    }
+
+    // Whether any of the values cached so far differ from `properties`
+    pub(crate) fn differs_from(&self, properties: &RtkitProperties) -> bool {
+        fn differs<T: PartialEq>(cell: &OnceLock<T>, value: &T) -> bool {
+            cell.get().is_some_and(|cached| cached != value)
+        }
+
+        differs(
+            &self.max_realtime_priority,
+            &properties.max_realtime_priority,
+        ) || differs(&self.min_nice_level, &properties.min_nice_level)
+            || differs(&self.rttime_usec_max, &properties.rttime_usec_max)
+    }
+}
+
+// Returns the value in `cell` if there is one, otherwise calls `fetch` and stores its result in
+// `cell` (if any).
+pub(crate) fn get_or_fetch<T, F>(cell: Option<&OnceLock<T>>, fetch: F) -> Result<T, RtkitError>
+where
+    T: Copy,
+    F: FnOnce() -> Result<T, RtkitError>,
+{
+    let Some(cell) = cell else {
+        return fetch();
+    };
+
+    if let Some(value) = cell.get() {
+        return Ok(*value);
+    }
+
+    let value = fetch()?;
+    let _ = cell.set(value);
+
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_or_fetch() {
+        let cell = OnceLock::new();
+
+        assert_eq!(get_or_fetch(Some(&cell), || Ok(20)).unwrap(), 20);
+        // The cached value is returned without fetching
+        assert_eq!(get_or_fetch(Some(&cell), || Ok(10)).unwrap(), 20);
+
+        // Errors are not cached
+        let cell = OnceLock::<i32>::new();
+        assert!(get_or_fetch(Some(&cell), || Err(RtkitError::DaemonUnavailable)).is_err());
+        assert_eq!(get_or_fetch(Some(&cell), || Ok(10)).unwrap(), 10);
+
+        // Without a cache, we always fetch
+        assert_eq!(get_or_fetch(None, || Ok(10)).unwrap(), 10);
+        assert_eq!(get_or_fetch(None, || Ok(20)).unwrap(), 20);
+    }
+}
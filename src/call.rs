@@ -0,0 +1,162 @@
+// SPDX-License-Identifier: MIT
+// SPDX-FileCopyrightText: Copyright (c) 2025 Asymptotic Inc.
+// SPDX-FileCopyrightText: Copyright (c) 2025 Sanchayan Maity
+
+// Helpers for making D-Bus calls to the daemon.
+
+use std::future::Future;
+use std::time::Duration;
+
+use futures_lite::StreamExt;
+use zbus::export::serde::ser::Serialize;
+use zbus::message::{Flags, Type};
+use zbus::zvariant::DynamicType;
+use zbus::{Message, MessageStream};
+
+use crate::RtkitError;
+
+// Makes a method call with the `ALLOW_INTERACTIVE_AUTHORIZATION` flag set, which
+// `zbus::Connection::call_method()` has no way to do, and waits for the reply.
+pub(crate) async fn call_interactive<B>(
+    connection: &zbus::Connection,
+    destination: &str,
+    path: &str,
+    interface: &str,
+    method: &str,
+    body: &B,
+) -> zbus::Result<Message>
+where
+    B: Serialize + DynamicType,
+{
+    let call = Message::method_call(path, method)?
+        .destination(destination)?
+        .interface(interface)?
+        .with_flags(Flags::AllowInteractiveAuth)?
+        .build(body)?;
+    let serial = call.primary_header().serial_num();
+
+    // Subscribe before sending, so that the reply cannot be missed
+    let mut stream = MessageStream::from(connection);
+    connection.send(&call).await?;
+
+    while let Some(message) = stream.next().await {
+        let message = message?;
+        if message.header().reply_serial() != Some(serial) {
+            continue;
+        }
+
+        return match message.message_type() {
+            Type::Error => Err(message.into()),
+            _ => Ok(message),
+        };
+    }
+
+    Err(zbus::Error::InputOutput(
+        std::io::Error::from(std::io::ErrorKind::BrokenPipe).into(),
+    ))
+}
+
+// Waits for the reply to a method call, failing with `RtkitError::Timeout` if there is none
+// within `timeout`.
+pub(crate) async fn with_timeout<F>(future: F, timeout: Duration) -> Result<Message, RtkitError>
+where
+    F: Future<Output = zbus::Result<Message>>,
+{
+    let reply = async { future.await.map_err(RtkitError::from) };
+    let timer = async {
+        async_io::Timer::after(timeout).await;
+        Err(RtkitError::Timeout)
+    };
+
+    futures_lite::future::or(reply, timer).await
+}
+
+// Calls `f`, and then retries it up to `retries` more times while it fails with a transient error
+// (see `RtkitError::is_transient()`). The delay before each retry starts at `backoff`, and doubles
+// after every attempt.
+pub(crate) fn retry<T, F>(retries: u32, backoff: Duration, mut f: F) -> Result<T, RtkitError>
+where
+    F: FnMut() -> Result<T, RtkitError>,
+{
+    let mut delay = backoff;
+
+    for _ in 0..retries {
+        match f() {
+            Err(e) if e.is_transient() => {
+                #[cfg(feature = "tracing")]
+                tracing::debug!(error = %e, ?delay, "retrying after transient failure");
+
+                std::thread::sleep(delay);
+                delay = delay.saturating_mul(2);
+            }
+            result => return result,
+        }
+    }
+
+    f()
+}
+
+#[cfg(feature = "tracing")]
+pub(crate) fn trace_result(result: &Result<Message, RtkitError>) {
+    match result {
+        Ok(_) => tracing::debug!("rtkit call succeeded"),
+        Err(e) => tracing::warn!(error = %e, "rtkit call failed"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_with_timeout() {
+        let pending = futures_lite::future::pending::<zbus::Result<Message>>();
+        let result = async_io::block_on(with_timeout(pending, Duration::from_millis(10)));
+        assert!(matches!(result, Err(RtkitError::Timeout)));
+
+        let ready = futures_lite::future::ready(Err(zbus::Error::InvalidReply));
+        let result = async_io::block_on(with_timeout(ready, Duration::from_secs(10)));
+        assert!(matches!(
+            result,
+            Err(RtkitError::Dbus(zbus::Error::InvalidReply))
+        ));
+    }
+
+    #[test]
+    fn test_retry() {
+        let transient = || RtkitError::Daemon {
+            name: "org.freedesktop.DBus.Error.NoReply".to_string(),
+            message: None,
+        };
+
+        // Succeeds on the last permitted attempt
+        let mut attempts = 0;
+        let result = retry(2, Duration::from_millis(1), || {
+            attempts += 1;
+            if attempts < 3 {
+                Err(transient())
+            } else {
+                Ok(attempts)
+            }
+        });
+        assert_eq!(result.unwrap(), 3);
+
+        // Gives up once the retries are exhausted
+        let mut attempts = 0;
+        let result: Result<(), _> = retry(2, Duration::from_millis(1), || {
+            attempts += 1;
+            Err(transient())
+        });
+        assert!(result.is_err());
+        assert_eq!(attempts, 3);
+
+        // Does not retry other errors
+        let mut attempts = 0;
+        let result: Result<(), _> = retry(2, Duration::from_millis(1), || {
+            attempts += 1;
+            Err(RtkitError::PermissionDenied { message: None })
+        });
+        assert!(matches!(result, Err(RtkitError::PermissionDenied { .. })));
+        assert_eq!(attempts, 1);
+    }
+}
@@ -0,0 +1,66 @@
+// SPDX-License-Identifier: MIT
+// SPDX-FileCopyrightText: Copyright (c) 2025 Asymptotic Inc.
+// SPDX-FileCopyrightText: Copyright (c) 2025 Sanchayan Maity
+
+use std::sync::{Mutex, OnceLock};
+
+use zbus::blocking::Connection;
+
+use crate::RtkitError;
+
+type Connect = Box<dyn Fn() -> Result<Connection, RtkitError> + Send + Sync>;
+
+// The connection to the daemon, shared by an `RTKit` and all its clones. For `RTKit::lazy()`, this
+// is only made on first use, and then kept for every later call.
+pub(crate) struct SharedConnection {
+    connection: OnceLock<Connection>,
+    // Makes the connection (and checks that the daemon is available), if it was deferred
+    connect: Option<Connect>,
+    // Held while connecting, so that concurrent first calls only connect once
+    connecting: Mutex<()>,
+}
+
+impl SharedConnection {
+    pub(crate) fn new(connection: Connection) -> SharedConnection {
+        SharedConnection {
+            connection: OnceLock::from(connection),
+            connect: None,
+            connecting: Mutex::new(()),
+        }
+    }
+
+    pub(crate) fn lazy<F>(connect: F) -> SharedConnection
+    where
+        F: Fn() -> Result<Connection, RtkitError> + Send + Sync + 'static,
+    {
+        SharedConnection {
+            connection: OnceLock::new(),
+            connect: Some(Box::new(connect)),
+            connecting: Mutex::new(()),
+        }
+    }
+
+    // Returns the connection, first making it if that was deferred. If this fails, the next call
+    // tries again.
+    pub(crate) fn get(&self) -> Result<&Connection, RtkitError> {
+        if let Some(connection) = self.connection.get() {
+            return Ok(connection);
+        }
+
+        let _connecting = self.connecting.lock().unwrap_or_else(|e| e.into_inner());
+
+        // Another thread may have connected while we were waiting
+        if let Some(connection) = self.connection.get() {
+            return Ok(connection);
+        }
+
+        let connect = self
+            .connect
+            .as_ref()
+            .expect("a connection is either given or deferred");
+
+        let connection = connect()?;
+
+        Ok(self.connection.get_or_init(|| connection))
+    }
+}
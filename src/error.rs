@@ -0,0 +1,585 @@
+// SPDX-License-Identifier: MIT
+// SPDX-FileCopyrightText: Copyright (c) 2025 Asymptotic Inc.
+// SPDX-FileCopyrightText: Copyright (c) 2025 Sanchayan Maity
+
+use crate::{BusKind, SchedPolicy};
+
+const DBUS_ERROR_ACCESS_DENIED: &str = "org.freedesktop.DBus.Error.AccessDenied";
+const POLKIT_ERROR_NOT_AUTHORIZED: &str = "org.freedesktop.PolicyKit1.Error.NotAuthorized";
+const POLKIT_ERROR_CANCELLED: &str = "org.freedesktop.PolicyKit1.Error.Cancelled";
+const DBUS_ERROR_FAILED: &str = "org.freedesktop.DBus.Error.Failed";
+// `rtkit` replies with `DBUS_ERROR_FAILED` and the text of `EBUSY` when a per-user limit is reached
+const EBUSY_MESSAGE: &str = "Device or resource busy";
+const DBUS_ERROR_INTERACTIVE_AUTHORIZATION_REQUIRED: &str =
+    "org.freedesktop.DBus.Error.InteractiveAuthorizationRequired";
+
+/// The errors that may be returned by this crate.
+///
+/// Error replies from the daemon are mapped to variants according to their D-Bus error name:
+///
+/// | D-Bus error                                                   | Variant                    |
+/// |---------------------------------------------------------------|----------------------------|
+/// | `org.freedesktop.DBus.Error.AccessDenied`                     | [`Self::PermissionDenied`] |
+/// | `org.freedesktop.DBus.Error.InteractiveAuthorizationRequired` | [`Self::NotAuthorized`]    |
+/// | `org.freedesktop.PolicyKit1.Error.NotAuthorized`              | [`Self::NotAuthorized`]    |
+/// | `org.freedesktop.PolicyKit1.Error.Cancelled`                  | [`Self::AuthDismissed`]    |
+/// | Any other error                                               | [`Self::Daemon`]           |
+///
+/// Note that `rtkit` itself reports any PolicyKit denial as `AccessDenied`. The other errors are
+/// only seen with implementations that pass them on, such as some sandbox proxies. With the
+/// `tracing` feature enabled, PolicyKit decisions are logged as they are mapped.
+///
+/// `rtkit` reports reaching one of its per-user limits as `org.freedesktop.DBus.Error.Failed`,
+/// with the message for `EBUSY` ("Device or resource busy"), which is mapped to
+/// [`Self::UserThreadLimitReached`].
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum RtkitError {
+    /// The `rtkit` daemon is not available on the bus.
+    #[error("the rtkit daemon is not available")]
+    DaemonUnavailable,
+    /// The request was denied, for example by PolicyKit.
+    #[error("permission denied by rtkit{}", detail(message))]
+    PermissionDenied {
+        /// The reason given by the daemon, if any.
+        message: Option<String>,
+    },
+    /// PolicyKit did not authorize the request.
+    #[error("not authorized by PolicyKit{}", detail(message))]
+    NotAuthorized {
+        /// The reason given by PolicyKit, if any.
+        message: Option<String>,
+    },
+    /// The daemon refused the request because one of its per-user limits was reached.
+    ///
+    /// By default, `rtkit` allows each user at most 25 real-time or high priority threads, in at
+    /// most 15 processes, and at most 25 requests in any 20 second period, refusing further
+    /// requests until threads exit or time passes. These limits are configured on the daemon's
+    /// command line (e.g. `--max-threads-per-user`), and are not exported over D-Bus, so which one
+    /// was reached cannot be told apart.
+    #[error("an rtkit per-user limit was reached{}", detail(message))]
+    UserThreadLimitReached {
+        /// The reason given by the daemon, if any.
+        message: Option<String>,
+    },
+    /// The user dismissed the PolicyKit authentication dialog.
+    #[error("PolicyKit authorization was dismissed by the user{}", detail(message))]
+    AuthDismissed {
+        /// The reason given by PolicyKit, if any.
+        message: Option<String>,
+    },
+    /// The daemon refused the request with a D-Bus error not covered by the other variants.
+    #[error("rtkit returned {name}{}", detail(message))]
+    Daemon {
+        /// The D-Bus error name, e.g. `org.freedesktop.DBus.Error.Failed`.
+        name: String,
+        /// The reason given by the daemon, if any.
+        message: Option<String>,
+    },
+    /// A D-Bus property had a different type than expected.
+    #[error("unexpected D-Bus variant type for {property}: expected {expected}")]
+    UnexpectedVariant {
+        /// The name of the property.
+        property: &'static str,
+        /// The type that was expected.
+        expected: &'static str,
+    },
+    /// The daemon does not export the named property, as is the case for some older versions.
+    #[error("the rtkit daemon does not support the {name} property")]
+    PropertyUnsupported {
+        /// The name of the property.
+        name: &'static str,
+    },
+    /// The daemon reported a value for the named property that is outside its valid range.
+    #[error("the rtkit daemon reported an invalid value of {value} for the {name} property")]
+    InvalidPropertyValue {
+        /// The name of the property.
+        name: &'static str,
+        /// The value that was reported.
+        value: i64,
+    },
+    /// The requested `RLIMIT_RTTIME` is higher than the maximum allowed by the daemon.
+    #[error("requested RLIMIT_RTTIME of {requested}us exceeds the rtkit maximum of {max}us")]
+    RttimeExceedsMax {
+        /// The requested limit, in microseconds.
+        requested: u64,
+        /// The maximum limit, in microseconds.
+        max: u64,
+    },
+    /// The current process is not permitted to change the `RLIMIT_RTTIME` of another process.
+    #[error("not permitted to set RLIMIT_RTTIME for process {process_id}")]
+    RlimitPermissionDenied {
+        /// The process id.
+        process_id: u64,
+    },
+    /// The requested nice level is outside the range permitted by the daemon.
+    #[error("nice level {requested} is outside the permitted range of {min} to 0")]
+    NiceLevelOutOfRange {
+        /// The requested nice level.
+        requested: i32,
+        /// The minimum nice level permitted by the daemon.
+        min: i32,
+    },
+    /// The requested nice level is lower than the current process's `RLIMIT_NICE` allows. See
+    /// [`RTKit::rlimit_min_nice_level()`](crate::RTKit::rlimit_min_nice_level).
+    #[error("nice level {requested} is below the minimum of {min} allowed by RLIMIT_NICE")]
+    NiceLevelExceedsRlimit {
+        /// The requested nice level.
+        requested: i32,
+        /// The minimum nice level allowed by `RLIMIT_NICE`.
+        min: i32,
+    },
+    /// The requested real-time priority is higher than the maximum permitted by the daemon.
+    #[error("real-time priority {requested} exceeds the rtkit maximum of {max}")]
+    RealtimePriorityOutOfRange {
+        /// The requested real-time priority.
+        requested: u32,
+        /// The maximum real-time priority permitted by the daemon.
+        max: u32,
+    },
+    /// `RLIMIT_RTTIME` is not set, or is set higher than the maximum allowed by the daemon, so a
+    /// request for real-time scheduling would be refused.
+    #[error(
+        "RLIMIT_RTTIME must be set to at most {max}us before requesting real-time scheduling \
+         (currently {})",
+        limit.map_or("unlimited".to_string(), |l| format!("{l}us"))
+    )]
+    RttimeLimitNotSet {
+        /// The current hard limit in microseconds, or `None` if it is unlimited.
+        limit: Option<u64>,
+        /// The maximum limit allowed by the daemon, in microseconds.
+        max: u64,
+    },
+    /// The thread does not exist (or does not belong to the specified process).
+    #[error("no such thread: {thread_id}")]
+    NoSuchThread {
+        /// The thread id that was looked for.
+        thread_id: u64,
+    },
+    /// The daemon reported success, but the thread does not have the requested real-time
+    /// scheduling.
+    #[error(
+        "thread {thread_id} was not made real-time (it has policy {policy:?} with priority \
+         {priority})"
+    )]
+    PromotionNotApplied {
+        /// The thread id.
+        thread_id: u64,
+        /// The thread's scheduling policy.
+        policy: SchedPolicy,
+        /// The thread's real-time priority.
+        priority: u32,
+    },
+    /// No thread in the current process has the given name.
+    #[error("no thread named {name:?} in the current process")]
+    NoThreadNamed {
+        /// The thread name that was looked for.
+        name: String,
+    },
+    /// The process is not permitted to change a thread's scheduling itself, e.g. because it lacks
+    /// the `CAP_SYS_NICE` capability. See
+    /// [`RTKit::make_thread_realtime_direct()`](crate::RTKit::make_thread_realtime_direct).
+    #[error("not permitted to change the scheduling of thread {thread_id} without rtkit")]
+    DirectPromotionDenied {
+        /// The thread id.
+        thread_id: u64,
+    },
+    /// A step of [`RTKit::go_realtime()`](crate::RTKit::go_realtime) failed.
+    #[error("failed to {step}: {source}")]
+    RealtimeSetupFailed {
+        /// The step that failed.
+        step: RealtimeSetupStep,
+        /// The error that caused it to fail.
+        #[source]
+        source: Box<RtkitError>,
+    },
+    /// The kernel reported a scheduling policy that is not known to this crate.
+    #[error("unknown scheduling policy {0}")]
+    UnknownSchedPolicy(u32),
+    /// No reply was received from the daemon within the configured timeout.
+    #[error("timed out waiting for a reply from rtkit")]
+    Timeout,
+    /// The kernel does not support `sched_getattr()` and `sched_setattr()`, which are needed to
+    /// read back or directly set a thread's scheduling, and were added in Linux 3.14.
+    #[error("the kernel is too old to read or set scheduling attributes (Linux 3.14 is required)")]
+    KernelTooOld,
+    /// The operation is not supported on this platform.
+    #[error("rtkit is not supported on this platform")]
+    Unsupported,
+    /// A [`thread_priority`](https://docs.rs/thread-priority) priority or policy has no
+    /// equivalent that can be requested from `rtkit`, e.g. `ThreadPriority::Max`, whose value
+    /// depends on the daemon's configuration.
+    #[cfg(feature = "thread-priority")]
+    #[error("{0} has no rtkit equivalent")]
+    ThreadPriorityNotConvertible(String),
+    /// Connecting to the D-Bus daemon failed, for example because it is not running.
+    #[error(
+        "failed to connect to the {bus} for rtkit: {source}{}",
+        connection_hint(source)
+    )]
+    BusConnectionFailed {
+        /// The bus that could not be connected to.
+        bus: BusKind,
+        /// The error from the connection attempt.
+        #[source]
+        source: zbus::Error,
+    },
+    /// Authenticating with the D-Bus daemon failed while connecting. This is usually because
+    /// the bus could not verify the caller's credentials, as can happen in some container setups.
+    /// See [`RTKitBuilder::auth_mechanism()`](crate::RTKitBuilder::auth_mechanism).
+    #[error("failed to authenticate with the D-Bus daemon: {0}")]
+    AuthFailed(#[source] zbus::Error),
+    /// A D-Bus error other than those listed above.
+    #[error("D-Bus error: {0}")]
+    Dbus(zbus::Error),
+    /// A system call failed.
+    #[error("system call failed: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+// The D-Bus errors that may be returned while the daemon is restarting, or the bus is busy
+const TRANSIENT_DBUS_ERRORS: &[&str] = &[
+    "org.freedesktop.DBus.Error.NoReply",
+    "org.freedesktop.DBus.Error.ServiceUnknown",
+    "org.freedesktop.DBus.Error.NameHasNoOwner",
+    "org.freedesktop.DBus.Error.Disconnected",
+];
+
+impl RtkitError {
+    // Whether the error is likely to be temporary, so that the operation may succeed if retried.
+    // Transport failures are only retried while connecting, as each attempt makes a new
+    // connection, whereas a call is retried on the same connection, which cannot recover from a
+    // broken socket.
+    pub(crate) fn is_transient(&self) -> bool {
+        match self {
+            RtkitError::Daemon { name, .. } => TRANSIENT_DBUS_ERRORS.contains(&name.as_str()),
+            RtkitError::BusConnectionFailed {
+                source: zbus::Error::InputOutput(_),
+                ..
+            } => true,
+            RtkitError::Dbus(zbus::Error::FDO(e)) => matches!(
+                **e,
+                zbus::fdo::Error::NoReply(_)
+                    | zbus::fdo::Error::ServiceUnknown(_)
+                    | zbus::fdo::Error::NameHasNoOwner(_)
+                    | zbus::fdo::Error::Disconnected(_)
+            ),
+            _ => false,
+        }
+    }
+}
+
+// Returns the error for a failed attempt to connect to `bus`
+pub(crate) fn connection_failed(bus: BusKind, err: zbus::Error) -> RtkitError {
+    match err {
+        zbus::Error::Handshake(_) => RtkitError::AuthFailed(err),
+        source => RtkitError::BusConnectionFailed { bus, source },
+    }
+}
+
+// Suggests a likely cause when the bus' socket is missing, or nothing is listening on it, which
+// is a common setup problem
+fn connection_hint(err: &zbus::Error) -> &'static str {
+    match err {
+        zbus::Error::InputOutput(e)
+            if matches!(
+                e.kind(),
+                std::io::ErrorKind::NotFound | std::io::ErrorKind::ConnectionRefused
+            ) =>
+        {
+            " (is the D-Bus daemon running?)"
+        }
+        _ => "",
+    }
+}
+
+// Formats the optional message attached to a D-Bus error reply as a suffix
+fn detail(message: &Option<String>) -> String {
+    message.as_ref().map_or(String::new(), |m| format!(": {m}"))
+}
+
+/// The steps performed by [`RTKit::go_realtime()`](crate::RTKit::go_realtime), as reported by
+/// [`RtkitError::RealtimeSetupFailed`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum RealtimeSetupStep {
+    /// Setting `RLIMIT_RTTIME` to the maximum permitted by the daemon.
+    SetRttimeLimit,
+    /// Clamping the requested priority to the maximum permitted by the daemon.
+    ClampPriority,
+    /// Requesting real-time scheduling for the thread.
+    Promote,
+}
+
+impl std::fmt::Display for RealtimeSetupStep {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            RealtimeSetupStep::SetRttimeLimit => "set RLIMIT_RTTIME",
+            RealtimeSetupStep::ClampPriority => "read the maximum real-time priority",
+            RealtimeSetupStep::Promote => "request real-time scheduling",
+        })
+    }
+}
+
+impl From<zbus::Error> for RtkitError {
+    fn from(err: zbus::Error) -> Self {
+        let err = map_zbus_error(err);
+
+        // Logged once the error is mapped, so that this happens however zbus decoded the reply
+        #[cfg(feature = "tracing")]
+        match &err {
+            RtkitError::PermissionDenied { message } => {
+                tracing::info!(?message, "request denied by rtkit or PolicyKit");
+            }
+            RtkitError::NotAuthorized { message } => {
+                tracing::info!(?message, "request not authorized by PolicyKit");
+            }
+            RtkitError::AuthDismissed { message } => {
+                tracing::info!(?message, "PolicyKit authorization dismissed");
+            }
+            _ => (),
+        }
+
+        err
+    }
+}
+
+// Maps error replies to variants as listed in the `RtkitError` documentation
+fn map_zbus_error(err: zbus::Error) -> RtkitError {
+    match err {
+        zbus::Error::MethodError(name, message, _) if name.as_str() == DBUS_ERROR_ACCESS_DENIED => {
+            RtkitError::PermissionDenied { message }
+        }
+        zbus::Error::MethodError(name, message, _)
+            if name.as_str() == POLKIT_ERROR_NOT_AUTHORIZED
+                || name.as_str() == DBUS_ERROR_INTERACTIVE_AUTHORIZATION_REQUIRED =>
+        {
+            RtkitError::NotAuthorized { message }
+        }
+        zbus::Error::MethodError(name, message, _) if name.as_str() == POLKIT_ERROR_CANCELLED => {
+            RtkitError::AuthDismissed { message }
+        }
+        zbus::Error::MethodError(name, message, _)
+            if name.as_str() == DBUS_ERROR_FAILED && message.as_deref() == Some(EBUSY_MESSAGE) =>
+        {
+            RtkitError::UserThreadLimitReached { message }
+        }
+        zbus::Error::MethodError(name, message, _) => RtkitError::Daemon {
+            name: name.to_string(),
+            message,
+        },
+        zbus::Error::FDO(e) => match *e {
+            zbus::fdo::Error::AccessDenied(message) => RtkitError::PermissionDenied {
+                message: Some(message),
+            },
+            zbus::fdo::Error::InteractiveAuthorizationRequired(message) => {
+                RtkitError::NotAuthorized {
+                    message: Some(message),
+                }
+            }
+            zbus::fdo::Error::Failed(message) if message == EBUSY_MESSAGE => {
+                RtkitError::UserThreadLimitReached {
+                    message: Some(message),
+                }
+            }
+            e => RtkitError::Dbus(zbus::Error::FDO(Box::new(e))),
+        },
+        err => RtkitError::Dbus(err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zbus::Message;
+
+    fn method_error(name: &str, detail: &str) -> zbus::Error {
+        let call = Message::method_call("/", "Test")
+            .unwrap()
+            .build(&())
+            .unwrap();
+
+        Message::error(&call.header(), name)
+            .unwrap()
+            .build(&detail)
+            .unwrap()
+            .into()
+    }
+
+    #[test]
+    fn test_permission_denied() {
+        let err = method_error(DBUS_ERROR_ACCESS_DENIED, "denied");
+        assert!(matches!(
+            RtkitError::from(err),
+            RtkitError::PermissionDenied { .. }
+        ));
+
+        let err = method_error("org.freedesktop.DBus.Error.Failed", "failed");
+        assert!(matches!(RtkitError::from(err), RtkitError::Daemon { .. }));
+
+        let err = zbus::fdo::Error::AccessDenied("denied".to_string());
+        assert!(matches!(
+            RtkitError::from(zbus::Error::from(err)),
+            RtkitError::PermissionDenied { .. }
+        ));
+
+        let err = zbus::fdo::Error::Failed("failed".to_string());
+        assert!(matches!(
+            RtkitError::from(zbus::Error::from(err)),
+            RtkitError::Dbus(_)
+        ));
+    }
+
+    #[test]
+    fn test_daemon_message() {
+        let err = method_error(
+            DBUS_ERROR_ACCESS_DENIED,
+            "The maximum realtime priority is 20",
+        );
+        assert_eq!(
+            RtkitError::from(err).to_string(),
+            "permission denied by rtkit: The maximum realtime priority is 20"
+        );
+
+        let err = method_error("org.freedesktop.DBus.Error.Failed", "No such process");
+        let err = RtkitError::from(err);
+        assert!(matches!(
+            &err,
+            RtkitError::Daemon { name, message: Some(message) }
+                if name == "org.freedesktop.DBus.Error.Failed" && message == "No such process"
+        ));
+        assert_eq!(
+            err.to_string(),
+            "rtkit returned org.freedesktop.DBus.Error.Failed: No such process"
+        );
+    }
+
+    #[test]
+    fn test_user_thread_limit_reached() {
+        let err = RtkitError::from(method_error(DBUS_ERROR_FAILED, EBUSY_MESSAGE));
+        assert!(matches!(err, RtkitError::UserThreadLimitReached { .. }));
+        assert_eq!(
+            err.to_string(),
+            "an rtkit per-user limit was reached: Device or resource busy"
+        );
+
+        let err = zbus::Error::FDO(Box::new(zbus::fdo::Error::Failed(EBUSY_MESSAGE.into())));
+        assert!(matches!(
+            RtkitError::from(err),
+            RtkitError::UserThreadLimitReached { .. }
+        ));
+
+        // Other failures are passed on as they are
+        assert!(matches!(
+            RtkitError::from(method_error(DBUS_ERROR_FAILED, "Invalid argument")),
+            RtkitError::Daemon { .. }
+        ));
+    }
+
+    #[test]
+    fn test_connection_failed() {
+        let missing = std::io::Error::from(std::io::ErrorKind::NotFound);
+        let err = connection_failed(BusKind::System, zbus::Error::InputOutput(missing.into()));
+        assert!(matches!(
+            err,
+            RtkitError::BusConnectionFailed {
+                bus: BusKind::System,
+                ..
+            }
+        ));
+        assert_eq!(
+            err.to_string(),
+            "failed to connect to the system bus for rtkit: I/O error: entity not found (is the \
+             D-Bus daemon running?)"
+        );
+
+        let err = connection_failed(BusKind::Session, zbus::Error::InvalidReply);
+        assert!(!err.to_string().contains("running"));
+
+        let err = connection_failed(BusKind::System, zbus::Error::Handshake("no".to_string()));
+        assert!(matches!(err, RtkitError::AuthFailed(_)));
+    }
+
+    #[test]
+    fn test_polkit() {
+        let err = method_error(POLKIT_ERROR_NOT_AUTHORIZED, "not authorized");
+        assert!(matches!(
+            RtkitError::from(err),
+            RtkitError::NotAuthorized { message: Some(m) } if m == "not authorized"
+        ));
+
+        let err = method_error(DBUS_ERROR_INTERACTIVE_AUTHORIZATION_REQUIRED, "no agent");
+        assert!(matches!(
+            RtkitError::from(err),
+            RtkitError::NotAuthorized { message: Some(m) } if m == "no agent"
+        ));
+
+        let err = method_error(POLKIT_ERROR_CANCELLED, "dismissed");
+        let err = RtkitError::from(err);
+        assert!(matches!(err, RtkitError::AuthDismissed { .. }));
+        assert_eq!(
+            err.to_string(),
+            "PolicyKit authorization was dismissed by the user: dismissed"
+        );
+    }
+
+    #[test]
+    fn test_is_transient() {
+        let err = method_error("org.freedesktop.DBus.Error.NoReply", "no reply");
+        assert!(RtkitError::from(err).is_transient());
+
+        let err = zbus::fdo::Error::ServiceUnknown("unknown".to_string());
+        assert!(RtkitError::from(zbus::Error::from(err)).is_transient());
+
+        let err = method_error(DBUS_ERROR_ACCESS_DENIED, "denied");
+        assert!(!RtkitError::from(err).is_transient());
+
+        let err = method_error("org.freedesktop.DBus.Error.InvalidArgs", "invalid");
+        assert!(!RtkitError::from(err).is_transient());
+
+        assert!(!RtkitError::Timeout.is_transient());
+
+        // A broken connection is only worth retrying when connecting again
+        let io = || zbus::Error::InputOutput(std::io::Error::other("reset").into());
+        assert!(!RtkitError::from(io()).is_transient());
+        assert!(
+            RtkitError::BusConnectionFailed {
+                bus: BusKind::System,
+                source: io(),
+            }
+            .is_transient()
+        );
+    }
+
+    #[test]
+    fn test_realtime_setup_failed() {
+        let err = RtkitError::RealtimeSetupFailed {
+            step: RealtimeSetupStep::Promote,
+            source: Box::new(RtkitError::PermissionDenied { message: None }),
+        };
+        assert_eq!(
+            err.to_string(),
+            "failed to request real-time scheduling: permission denied by rtkit"
+        );
+        assert!(std::error::Error::source(&err).is_some());
+    }
+
+    #[test]
+    fn test_rttime_limit_not_set() {
+        let err = RtkitError::RttimeLimitNotSet {
+            limit: None,
+            max: 200000,
+        };
+        assert_eq!(
+            err.to_string(),
+            "RLIMIT_RTTIME must be set to at most 200000us before requesting real-time \
+             scheduling (currently unlimited)"
+        );
+
+        let err = RtkitError::RttimeLimitNotSet {
+            limit: Some(300000),
+            max: 200000,
+        };
+        assert!(err.to_string().ends_with("(currently 300000us)"));
+    }
+}
@@ -0,0 +1,70 @@
+// SPDX-License-Identifier: MIT
+// SPDX-FileCopyrightText: Copyright (c) 2025 Asymptotic Inc.
+// SPDX-FileCopyrightText: Copyright (c) 2025 Sanchayan Maity
+
+use std::fmt;
+
+/// A kernel thread id, as returned by `gettid()` and
+/// [`RTKit::current_thread_id()`](crate::RTKit::current_thread_id).
+///
+/// This is distinct from [`ProcessId`], so that the two cannot be swapped by mistake, e.g. in
+/// [`RTKit::make_thread_realtime_with_pid()`](crate::RTKit::make_thread_realtime_with_pid). It is
+/// unrelated to [`std::thread::ThreadId`], which is not visible to the kernel.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ThreadId(u64);
+
+impl ThreadId {
+    /// Returns the thread id as an integer.
+    pub fn get(self) -> u64 {
+        self.0
+    }
+}
+
+impl From<u64> for ThreadId {
+    fn from(thread_id: u64) -> ThreadId {
+        ThreadId(thread_id)
+    }
+}
+
+impl From<ThreadId> for u64 {
+    fn from(thread_id: ThreadId) -> u64 {
+        thread_id.0
+    }
+}
+
+impl fmt::Display for ThreadId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// A process id, as returned by [`RTKit::current_process_id()`](crate::RTKit::current_process_id).
+///
+/// This is distinct from [`ThreadId`], so that the two cannot be swapped by mistake.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ProcessId(u64);
+
+impl ProcessId {
+    /// Returns the process id as an integer.
+    pub fn get(self) -> u64 {
+        self.0
+    }
+}
+
+impl From<u64> for ProcessId {
+    fn from(process_id: u64) -> ProcessId {
+        ProcessId(process_id)
+    }
+}
+
+impl From<ProcessId> for u64 {
+    fn from(process_id: ProcessId) -> u64 {
+        process_id.0
+    }
+}
+
+impl fmt::Display for ProcessId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
@@ -0,0 +1,14 @@
+// SPDX-License-Identifier: MIT
+// SPDX-FileCopyrightText: Copyright (c) 2025 Asymptotic Inc.
+// SPDX-FileCopyrightText: Copyright (c) 2025 Sanchayan Maity
+
+/// Identifies the `rtkit` daemon instance on the bus, as returned by
+/// [`RTKit::daemon_info()`](crate::RTKit::daemon_info).
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct DaemonInfo {
+    /// The unique bus name (e.g. `:1.42`) of the connection that owns the `rtkit` service name.
+    pub unique_name: String,
+    /// The process id of the daemon, or `None` if the bus could not report it.
+    pub pid: Option<u32>,
+}
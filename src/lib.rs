@@ -5,262 +5,3376 @@
 #![warn(missing_docs)]
 #![doc = include_str!("../README.md")]
 
+use std::ops::RangeInclusive;
+use std::sync::Arc;
+use std::time::Duration;
+
+use zbus::Message;
 use zbus::blocking::Connection;
-use zbus::zvariant::Value;
-use zbus::Result;
+use zbus::export::serde::ser::Serialize;
+use zbus::zvariant::{DynamicType, Type, Value};
+
+#[cfg(feature = "anyhow")]
+pub mod anyhow;
+
+mod availability;
+
+mod builder;
+use builder::Config;
+pub use builder::{BusKind, RTKitBuilder};
+
+mod cache;
+
+mod call;
+
+mod connection;
+use connection::SharedConnection;
+
+use cache::PropertyCache;
+
+mod error;
+pub use error::{RealtimeSetupStep, RtkitError};
+
+mod id;
+pub use id::{ProcessId, ThreadId};
+
+mod info;
+pub use info::DaemonInfo;
+
+pub mod prelude;
+
+mod priority;
+pub use priority::{NiceLevel, RealtimePriority};
+
+mod properties;
+pub use properties::RtkitProperties;
+
+#[cfg(all(feature = "test-util", unix))]
+pub mod mock;
+#[cfg(all(feature = "test-util", unix))]
+pub use mock::{MockRequest, MockRtkit};
+
+mod promotion;
+
+mod proxy;
+pub use promotion::{PromotionOutcome, PromotionReport, RealtimePromotion, RealtimeToken};
+
+mod reset;
+use reset::ResetKnownOnDrop;
+
+mod sched;
+pub use sched::SchedPolicy;
+
+mod sys;
+use sys::SchedAttr;
+
+#[cfg(all(feature = "thread-priority", target_os = "linux"))]
+mod thread_priority;
+
+#[cfg(feature = "async")]
+mod r#async;
+#[cfg(feature = "async")]
+pub use r#async::AsyncRTKit;
+
+const RTKIT_OBJECT_PATH: &str = "/org/freedesktop/RealtimeKit1";
+const RTKIT_SERVICE_NAME: &str = "org.freedesktop.RealtimeKit1";
+const RTKIT_INTERFACE: &str = "org.freedesktop.RealtimeKit1";
+
+const DBUS_SERVICE_NAME: &str = "org.freedesktop.DBus";
+const DBUS_OBJECT_PATH: &str = "/org/freedesktop/DBus";
+const DBUS_ERROR_NAME_HAS_NO_OWNER: &str = "org.freedesktop.DBus.Error.NameHasNoOwner";
+const DBUS_ERROR_UNKNOWN_METHOD: &str = "org.freedesktop.DBus.Error.UnknownMethod";
+const DBUS_ERROR_NOT_SUPPORTED: &str = "org.freedesktop.DBus.Error.NotSupported";
+
+// The blocking counterparts of the `availability` checks
+fn is_rtkit_available(
+    connection: &Connection,
+    service_name: &str,
+    object_path: &str,
+) -> zbus::Result<bool> {
+    async_io::block_on(availability::is_rtkit_available(
+        connection.inner(),
+        service_name,
+        object_path,
+    ))
+}
+
+fn ensure_rtkit_available(
+    connection: &Connection,
+    service_name: &str,
+    object_path: &str,
+    auto_start: bool,
+) -> zbus::Result<bool> {
+    async_io::block_on(availability::ensure_rtkit_available(
+        connection.inner(),
+        service_name,
+        object_path,
+        auto_start,
+    ))
+}
+
+// Fails unless the daemon is available on `connection`, first trying to start it if `auto_start`
+// is set
+fn ensure_available(connection: &Connection, config: &Config) -> Result<(), RtkitError> {
+    if !cfg!(target_os = "linux") {
+        return Err(RtkitError::Unsupported);
+    }
+
+    if !ensure_rtkit_available(
+        connection,
+        &config.service_name,
+        &config.object_path,
+        config.auto_start,
+    )? {
+        return Err(RtkitError::DaemonUnavailable);
+    }
+
+    Ok(())
+}
+
+fn property_from_value<'a, T>(name: &'static str, value: &'a Value<'a>) -> Result<T, RtkitError>
+where
+    T: TryFrom<&'a Value<'a>>,
+{
+    T::try_from(value).map_err(|_| RtkitError::UnexpectedVariant {
+        property: name,
+        expected: std::any::type_name::<T>(),
+    })
+}
+
+// Returns the name of the D-Bus error reply behind `err`, whether zbus decoded it as a generic
+// method error or as one of the standard `org.freedesktop.DBus.Error` errors
+fn dbus_error_name(err: &RtkitError) -> Option<String> {
+    match err {
+        RtkitError::Daemon { name, .. } => Some(name.clone()),
+        RtkitError::Dbus(zbus::Error::FDO(e)) => Some(zbus::DBusError::name(&**e).to_string()),
+        _ => None,
+    }
+}
+
+// Checks that `thread_id` is a thread of `process_id` (or of the current process, if `None`)
+fn check_thread_exists(
+    process_id: Option<ProcessId>,
+    thread_id: ThreadId,
+) -> Result<(), RtkitError> {
+    let thread_id = thread_id.get();
+    let task_dir = match process_id {
+        Some(process_id) => format!("/proc/{process_id}/task"),
+        None => "/proc/self/task".to_string(),
+    };
+
+    if !sys::task_exists(&task_dir, thread_id)? {
+        return Err(RtkitError::NoSuchThread { thread_id });
+    }
+
+    Ok(())
+}
+
+// Converts an `RLIMIT_NICE` value to the lowest nice level it allows, clamped to the range of
+// valid nice levels (-20 to 19)
+fn min_nice_level_for_rlimit(limit: u64) -> i32 {
+    (20 - limit.min(40) as i32).min(19)
+}
+
+// Checks that the thread has a real-time policy with the given priority
+fn verify_realtime(thread_id: ThreadId, priority: RealtimePriority) -> Result<(), RtkitError> {
+    let attr = sys::sched_getattr(thread_id.get())?;
+    let policy = SchedPolicy::try_from(attr.policy)?;
+
+    if !policy.is_realtime() || attr.priority != priority.get() {
+        return Err(RtkitError::PromotionNotApplied {
+            thread_id: thread_id.get(),
+            policy,
+            priority: attr.priority,
+        });
+    }
+
+    Ok(())
+}
+
+// The result of a request for each of several threads
+type ThreadResults = Vec<(ThreadId, Result<(), RtkitError>)>;
+
+// Sets the scheduling parameters of `thread_id` without going through the daemon, for the
+// `*_direct()` methods
+fn set_sched_attr_direct(thread_id: ThreadId, attr: &SchedAttr) -> Result<(), RtkitError> {
+    sys::sched_setattr(thread_id.get(), attr).map_err(|err| direct_error(thread_id, err))
+}
+
+// Reports `EPERM` and `EACCES` from a direct change as `RtkitError::DirectPromotionDenied`
+fn direct_error(thread_id: ThreadId, err: RtkitError) -> RtkitError {
+    match err {
+        RtkitError::Io(e) if e.kind() == std::io::ErrorKind::PermissionDenied => {
+            RtkitError::DirectPromotionDenied {
+                thread_id: thread_id.get(),
+            }
+        }
+        err => err,
+    }
+}
+
+/// The top-level structure providing access to the crate's functionality.
+///
+/// `RTKit` can be cheaply cloned, with all clones sharing the same underlying D-Bus connection. If
+/// property caching is enabled, each clone starts with a copy of the values cached so far, but
+/// maintains its own cache from then on.
+///
+/// `RTKit` is `Send` and `Sync`, so a single instance may be shared between threads (e.g. in an
+/// `Arc`), and its methods called concurrently. Calls made from different threads are sent over
+/// the shared connection independently, and each waits only for its own reply.
+#[derive(Clone)]
+pub struct RTKit {
+    connection: Arc<SharedConnection>,
+    config: Config,
+    cache: Option<PropertyCache>,
+    reset_known_on_drop: Option<Arc<ResetKnownOnDrop>>,
+}
+
+// The connection is deliberately omitted, so that the output is stable, and does not include
+// details such as its socket or unique name
+impl std::fmt::Debug for RTKit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RTKit")
+            .field("service", &self.config.service_name)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The same as [`RTKit::from_connection()`], including checking that the daemon is available.
+impl TryFrom<Connection> for RTKit {
+    type Error = RtkitError;
+
+    fn try_from(connection: Connection) -> Result<RTKit, RtkitError> {
+        RTKit::from_connection(connection)
+    }
+}
+
+// Ensure that `RTKit` stays `Send` and `Sync`, as documented above
+const _: fn() = || {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<RTKit>();
+};
+
+impl RTKit {
+    /// Create an instance of the `RTKit` structure. This makes a connection to the system D-Bus
+    /// daemon, and ensures that the `rtkit` daemon is available.
+    ///
+    /// Availability is checked by looking for the `rtkit` service in the bus' `ListNames`. If the
+    /// bus policy does not allow that, reading one of the daemon's properties is tried instead.
+    ///
+    /// Returns an `RTKit` structure if the connection succeeds and the daemon is available, or an
+    /// error otherwise.
+    pub fn new() -> Result<RTKit, RtkitError> {
+        Self::builder().build()
+    }
+
+    /// Create an instance of the `RTKit` structure, like [`new()`](Self::new), returning `None`
+    /// if that fails for any reason, e.g. because the daemon is unavailable.
+    ///
+    /// This suits applications that use real-time scheduling when they can, but continue without
+    /// it otherwise. The reason for any failure is logged via `tracing`, if the feature is
+    /// enabled.
+    pub fn try_new() -> Option<RTKit> {
+        let result = Self::new();
+
+        #[cfg(feature = "tracing")]
+        if let Err(e) = &result {
+            tracing::info!(error = %e, "rtkit not available, continuing without it");
+        }
+
+        result.ok()
+    }
+
+    /// Create an instance of the `RTKit` structure using an existing D-Bus `connection`, which
+    /// should usually be to the system bus. This ensures that the `rtkit` daemon is available.
+    ///
+    /// Returns an `RTKit` structure if the daemon is available, or an error otherwise.
+    pub fn from_connection(connection: Connection) -> Result<RTKit, RtkitError> {
+        Self::from_parts(connection, Config::default())
+    }
+
+    /// Create an instance of the `RTKit` structure, like [`new()`](Self::new), which caches the
+    /// daemon's properties ([`max_realtime_priority()`](Self::max_realtime_priority),
+    /// [`min_nice_level()`](Self::min_nice_level) and [`rttime_usec_max()`](Self::rttime_usec_max))
+    /// after they are first read.
+    ///
+    /// These properties are fixed for the lifetime of the `rtkit` daemon, so caching them saves a
+    /// D-Bus round-trip on every subsequent read. However, if the daemon is restarted with a
+    /// different configuration, the cached values will be stale until
+    /// [`invalidate_cache()`](Self::invalidate_cache) is called.
+    pub fn new_cached() -> Result<RTKit, RtkitError> {
+        Self::builder().cache_properties(true).build()
+    }
+
+    /// Clears any cached property values, so that they are read from the daemon again on next
+    /// access. This has no effect if property caching is not enabled.
+    pub fn invalidate_cache(&mut self) {
+        if let Some(cache) = &mut self.cache {
+            *cache = PropertyCache::default();
+        }
+    }
+
+    /// Returns whether the `rtkit` daemon is available on the system bus, without creating an
+    /// `RTKit` instance. This is always `false` on platforms other than Linux.
+    ///
+    /// Returns an error if connecting to the system bus fails.
+    pub fn is_available() -> Result<bool, RtkitError> {
+        if !cfg!(target_os = "linux") {
+            return Ok(false);
+        }
+
+        let connection =
+            Connection::system().map_err(|err| error::connection_failed(BusKind::System, err))?;
+
+        Ok(is_rtkit_available(
+            &connection,
+            RTKIT_SERVICE_NAME,
+            RTKIT_OBJECT_PATH,
+        )?)
+    }
+
+    /// Asks the system bus to start the `rtkit` daemon via D-Bus activation, if it is not already
+    /// running. This is the same as what [`RTKitBuilder::auto_start()`] does when building.
+    ///
+    /// Returns [`RtkitError::DaemonUnavailable`] if the daemon cannot be activated, e.g. because
+    /// it is not installed, or an error if connecting to the system bus fails.
+    pub fn activate() -> Result<(), RtkitError> {
+        if !cfg!(target_os = "linux") {
+            return Err(RtkitError::Unsupported);
+        }
+
+        let connection =
+            Connection::system().map_err(|err| error::connection_failed(BusKind::System, err))?;
+
+        if !async_io::block_on(availability::activate(
+            connection.inner(),
+            RTKIT_SERVICE_NAME,
+        ))? {
+            return Err(RtkitError::DaemonUnavailable);
+        }
+
+        Ok(())
+    }
+
+    /// Returns the underlying D-Bus connection, for making other calls (for example, to
+    /// PolicyKit) without opening a second connection.
+    ///
+    /// The connection is shared with this `RTKit` and any clones of it, so callers should not
+    /// close it, release names on it, or otherwise change it in ways that would affect calls made
+    /// by `RTKit`.
+    ///
+    /// For an `RTKit` created with [`lazy()`](Self::lazy), this connects first if that has not
+    /// been done yet, and so may fail.
+    pub fn connection(&self) -> Result<&Connection, RtkitError> {
+        self.connection.get()
+    }
+
+    /// Returns a view of this `RTKit` whose calls to the daemon wait at most `timeout` for a
+    /// reply, failing with [`RtkitError::Timeout`] otherwise. This overrides any timeout set with
+    /// [`RTKitBuilder::timeout()`], only for calls made through the returned view.
+    ///
+    /// This is useful when some calls need a different deadline to the rest, such as a slow
+    /// [`reset_all()`](Self::reset_all):
+    ///
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # fn main() -> Result<(), rtkit_rs::RtkitError> {
+    /// let rtkit = rtkit_rs::RTKit::builder()
+    ///     .timeout(Duration::from_millis(500))
+    ///     .build()?;
+    ///
+    /// rtkit.with_timeout(Duration::from_secs(10)).reset_all()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// The view is a clone of this `RTKit`, sharing the same connection.
+    pub fn with_timeout(&self, timeout: Duration) -> RTKit {
+        let mut rtkit = self.clone();
+        rtkit.config.timeout = Some(timeout);
+
+        rtkit
+    }
+
+    /// Returns the bus that this `RTKit` is connected to. This is useful to find out which bus was
+    /// used when [`RTKitBuilder::session_bus_fallback()`] is enabled, e.g. when debugging
+    /// container setups.
+    pub fn bus_kind(&self) -> BusKind {
+        self.config.bus_kind.clone()
+    }
+
+    /// Returns an [`RTKitBuilder`] to configure how the `rtkit` daemon is reached.
+    pub fn builder() -> RTKitBuilder {
+        RTKitBuilder::new()
+    }
+
+    /// Create an instance of the `RTKit` structure, like [`new()`](Self::new), but without
+    /// connecting to the system bus until the first call that needs the daemon. The connection,
+    /// and the check that the daemon is available, are then kept for all later calls.
+    ///
+    /// This allows an `RTKit` to be created early, e.g. as part of an application's configuration,
+    /// before the D-Bus environment is ready. The trade-off is that any of the methods that talk
+    /// to the daemon may now fail for the reasons that `new()` would, such as
+    /// [`RtkitError::DaemonUnavailable`]. If the first attempt to connect fails, the next call
+    /// tries again.
+    pub fn lazy() -> RTKit {
+        let config = Config {
+            bus_kind: BusKind::System,
+            ..Config::default()
+        };
+
+        Self::from_lazy(config, || {
+            Connection::system().map_err(|err| error::connection_failed(BusKind::System, err))
+        })
+    }
+
+    /// Create an instance of the `RTKit` structure connected to the system bus, without checking
+    /// whether the `rtkit` daemon is available, unlike [`new()`](Self::new).
+    ///
+    /// This suits services that start before `rtkit` does, and want to keep a single connection
+    /// while waiting for it, checking with [`check_available()`](Self::check_available) as often
+    /// as needed. Until the daemon is available, methods that talk to it fail.
+    pub fn connect() -> Result<RTKit, RtkitError> {
+        if !cfg!(target_os = "linux") {
+            return Err(RtkitError::Unsupported);
+        }
+
+        let config = Config {
+            bus_kind: BusKind::System,
+            ..Config::default()
+        };
+
+        Ok(Self::from_shared(
+            SharedConnection::new(
+                Connection::system()
+                    .map_err(|err| error::connection_failed(BusKind::System, err))?,
+            ),
+            config,
+        ))
+    }
+
+    /// Returns whether the `rtkit` daemon is currently available on this `RTKit`'s bus, using the
+    /// same check as [`new()`](Self::new). This can be called repeatedly, for example after
+    /// [`connect()`](Self::connect), without reconnecting.
+    pub fn check_available(&self) -> Result<bool, RtkitError> {
+        Ok(is_rtkit_available(
+            self.connection.get()?,
+            &self.config.service_name,
+            &self.config.object_path,
+        )?)
+    }
+
+    fn from_parts(connection: Connection, config: Config) -> Result<RTKit, RtkitError> {
+        ensure_available(&connection, &config)?;
+
+        Ok(Self::from_shared(SharedConnection::new(connection), config))
+    }
+
+    // Creates an `RTKit` whose connection is made by `connect` on first use
+    fn from_lazy<F>(config: Config, connect: F) -> RTKit
+    where
+        F: Fn() -> Result<Connection, RtkitError> + Send + Sync + 'static,
+    {
+        let checked = config.clone();
+        let connection = SharedConnection::lazy(move || {
+            let connection = connect()?;
+            ensure_available(&connection, &checked)?;
+
+            Ok(connection)
+        });
+
+        Self::from_shared(connection, config)
+    }
+
+    fn from_shared(connection: SharedConnection, config: Config) -> RTKit {
+        let cache = config.cache_properties.then(PropertyCache::default);
+
+        let mut rtkit = RTKit {
+            connection: Arc::new(connection),
+            config,
+            cache,
+            reset_known_on_drop: None,
+        };
+
+        if rtkit.config.reset_known_on_drop {
+            rtkit.reset_known_on_drop = Some(Arc::new(ResetKnownOnDrop::new(rtkit.clone())));
+        }
+
+        rtkit
+    }
+
+    fn call<B>(&self, request: &proxy::Request<'_, B>) -> Result<Message, RtkitError>
+    where
+        B: Serialize + DynamicType + std::fmt::Debug,
+    {
+        let &proxy::Request {
+            interface,
+            method,
+            ref body,
+        } = request;
+
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("rtkit_call", interface, method, args = ?body).entered();
+
+        let destination = Some(self.config.service_name.as_str());
+        let path = self.config.object_path.as_str();
+
+        let result = call::retry(self.config.retries, self.config.retry_backoff, || {
+            let connection = self.connection.get()?;
+
+            if self.config.interactive_authorization {
+                let future = call::call_interactive(
+                    connection.inner(),
+                    &self.config.service_name,
+                    path,
+                    interface,
+                    method,
+                    body,
+                );
+
+                return match self.config.timeout {
+                    None => async_io::block_on(future).map_err(RtkitError::from),
+                    Some(timeout) => async_io::block_on(call::with_timeout(future, timeout)),
+                };
+            }
+
+            match self.config.timeout {
+                None => connection
+                    .call_method(destination, path, Some(interface), method, body)
+                    .map_err(RtkitError::from),
+                Some(timeout) => async_io::block_on(call::with_timeout(
+                    connection.inner().call_method(
+                        destination,
+                        path,
+                        Some(interface),
+                        method,
+                        body,
+                    ),
+                    timeout,
+                )),
+            }
+        });
+
+        #[cfg(feature = "tracing")]
+        call::trace_result(&result);
+
+        result
+    }
+
+    // Calls `method` on the bus daemon itself, with the same timeout and retries as `call()`
+    fn call_bus<B>(&self, method: &str, body: &B) -> Result<Message, RtkitError>
+    where
+        B: Serialize + DynamicType,
+    {
+        let destination = Some(DBUS_SERVICE_NAME);
+
+        call::retry(self.config.retries, self.config.retry_backoff, || {
+            let connection = self.connection.get()?;
+
+            match self.config.timeout {
+                None => connection
+                    .call_method(destination, DBUS_OBJECT_PATH, destination, method, body)
+                    .map_err(RtkitError::from),
+                Some(timeout) => async_io::block_on(call::with_timeout(
+                    connection.inner().call_method(
+                        destination,
+                        DBUS_OBJECT_PATH,
+                        destination,
+                        method,
+                        body,
+                    ),
+                    timeout,
+                )),
+            }
+        })
+    }
+
+    fn property<T>(&self, name: &'static str) -> Result<T, RtkitError>
+    where
+        T: for<'a> TryFrom<&'a Value<'a>>,
+    {
+        let message = self
+            .call(&proxy::get_property(name))
+            .map_err(|err| proxy::property_error(name, err))?;
+
+        proxy::parse_property(name, &message)
+    }
+
+    /// Returns the maximum permitted real-time priority value.
+    pub fn max_realtime_priority(&self) -> Result<i32, RtkitError> {
+        let cell = self
+            .cache
+            .as_ref()
+            .map(|cache| &cache.max_realtime_priority);
+
+        cache::get_or_fetch(cell, || self.property("MaxRealtimePriority"))
+    }
+
+    /// Returns the maximum permitted real-time priority value as a `u32`, as taken by
+    /// [`make_thread_realtime()`](Self::make_thread_realtime) and related methods.
+    ///
+    /// Returns [`RtkitError::InvalidPropertyValue`] if the daemon reports a negative value.
+    pub fn max_realtime_priority_u32(&self) -> Result<u32, RtkitError> {
+        let max = self.max_realtime_priority()?;
+
+        u32::try_from(max).map_err(|_| RtkitError::InvalidPropertyValue {
+            name: "MaxRealtimePriority",
+            value: max.into(),
+        })
+    }
+
+    /// Returns the minimum permitted nice level value.
+    pub fn min_nice_level(&self) -> Result<i32, RtkitError> {
+        let cell = self.cache.as_ref().map(|cache| &cache.min_nice_level);
+
+        cache::get_or_fetch(cell, || self.property("MinNiceLevel"))
+    }
+
+    /// Returns the maximum time (in microseconds) that may be set for `RLIMIT_RTTIME`. This is the
+    /// maximum time a real-time thread may continuously occupy the CPU before being blocked by a
+    /// system call.
+    ///
+    /// Applications _must_ set an `RTLIMIT_RTTIME` before attempting to request real-time
+    /// priority.
+    pub fn rttime_usec_max(&self) -> Result<i64, RtkitError> {
+        let cell = self.cache.as_ref().map(|cache| &cache.rttime_usec_max);
+
+        cache::get_or_fetch(cell, || self.property("RTTimeUSecMax"))
+    }
+
+    /// Returns all the daemon's properties, using a single D-Bus call.
+    ///
+    /// If property caching is enabled, this also updates the cached values.
+    pub fn properties(&self) -> Result<RtkitProperties, RtkitError> {
+        let message = self.call(&proxy::get_all_properties())?;
+        let properties = proxy::parse_properties(&message)?;
+
+        if let Some(cache) = &self.cache {
+            let _ = cache
+                .max_realtime_priority
+                .set(properties.max_realtime_priority);
+            let _ = cache.min_nice_level.set(properties.min_nice_level);
+            let _ = cache.rttime_usec_max.set(properties.rttime_usec_max);
+        }
+
+        Ok(properties)
+    }
+
+    /// Returns a one-line summary of the daemon's limits, for recording in logs at startup, e.g.
+    /// `rtkit: max_rt_prio=20, min_nice=-15, rttime_max=200000us`.
+    ///
+    /// This is the [`Display`](std::fmt::Display) format of the
+    /// [`properties()`](Self::properties), which is stable.
+    pub fn limits_summary(&self) -> Result<String, RtkitError> {
+        Ok(self.properties()?.to_string())
+    }
+
+    /// Re-reads all the daemon's properties with a single D-Bus call, replacing any cached
+    /// values, and returns whether any of them changed from what was cached.
+    ///
+    /// The properties are fixed for the lifetime of a daemon, so a change (typically in
+    /// [`max_realtime_priority()`](Self::max_realtime_priority)) signals that the daemon has been
+    /// reconfigured and restarted. Long-running services can call this when
+    /// `AsyncRTKit::watch_availability()` (with the `async` feature) reports the daemon
+    /// reappearing, to decide whether their threads need to be promoted again.
+    ///
+    /// Values that had not been read yet do not count as changed. If property caching is not
+    /// enabled, this always returns `false`.
+    pub fn refresh(&mut self) -> Result<bool, RtkitError> {
+        let message = self.call(&proxy::get_all_properties())?;
+        let properties = proxy::parse_properties(&message)?;
+
+        let Some(cache) = &mut self.cache else {
+            return Ok(false);
+        };
+
+        let changed = cache.differs_from(&properties);
+        *cache = PropertyCache::from_properties(&properties);
+
+        Ok(changed)
+    }
+
+    /// Returns the unique bus name and process id of the `rtkit` daemon that is answering
+    /// requests, for diagnostic purposes.
+    ///
+    /// The process id is `None` if the bus does not support looking it up. Returns
+    /// [`RtkitError::DaemonUnavailable`] if the service is no longer on the bus.
+    pub fn daemon_info(&self) -> Result<DaemonInfo, RtkitError> {
+        let unique_name: String = match self.call_bus("GetNameOwner", &(&self.config.service_name,))
+        {
+            Ok(message) => message.body().deserialize()?,
+            Err(err) if dbus_error_name(&err).as_deref() == Some(DBUS_ERROR_NAME_HAS_NO_OWNER) => {
+                return Err(RtkitError::DaemonUnavailable);
+            }
+            Err(err) => return Err(err),
+        };
+
+        let pid = match self.call_bus("GetConnectionUnixProcessID", &(&unique_name,)) {
+            Ok(message) => Some(message.body().deserialize()?),
+            Err(err)
+                if matches!(
+                    dbus_error_name(&err).as_deref(),
+                    Some(DBUS_ERROR_UNKNOWN_METHOD | DBUS_ERROR_NOT_SUPPORTED)
+                ) =>
+            {
+                None
+            }
+            Err(err) => return Err(err),
+        };
+
+        Ok(DaemonInfo { unique_name, pid })
+    }
+
+    /// Returns the version or implementation string exported by the daemon in its `Version`
+    /// property, for enabling or disabling features that differ between rtkit-compatible daemons.
+    ///
+    /// The reference `rtkit` implementation does not export such a property, so this returns
+    /// `None` when it is absent, rather than [`RtkitError::PropertyUnsupported`].
+    pub fn implementation(&self) -> Result<Option<String>, RtkitError> {
+        match self.property("Version") {
+            Ok(version) => Ok(Some(version)),
+            Err(RtkitError::PropertyUnsupported { .. }) => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Sets `RLIMIT_RTTIME` for the current process to the maximum permitted by the daemon (see
+    /// [`rttime_usec_max()`](Self::rttime_usec_max)), and returns the value that was applied.
+    ///
+    /// Both the soft (`rlim_cur`) and hard (`rlim_max`) limits are set. This returns an error if
+    /// `setrlimit()` fails, for example if the hard limit is already lower than the daemon maximum
+    /// (an unprivileged process may not raise its hard limit).
+    pub fn apply_max_rttime_limit(&self) -> Result<u64, RtkitError> {
+        let rttime_max = self.rttime_usec_max()?.max(0) as u64;
+
+        sys::set_rttime_limit(rttime_max)?;
+
+        Ok(rttime_max)
+    }
+
+    /// Sets both the soft and hard `RLIMIT_RTTIME` of the specified process to `rttime_usec`
+    /// microseconds, for example before promoting one of its threads with
+    /// [`make_thread_realtime_with_pid()`](Self::make_thread_realtime_with_pid).
+    ///
+    /// This uses `prlimit()`, which requires the caller to have the same user id as the target
+    /// process (or `CAP_SYS_RESOURCE`, to raise its hard limit). Returns
+    /// [`RtkitError::RlimitPermissionDenied`] if this is not permitted.
+    pub fn prlimit_rttime(
+        process_id: impl Into<ProcessId>,
+        rttime_usec: u64,
+    ) -> Result<(), RtkitError> {
+        let process_id = process_id.into();
+        sys::set_process_rttime_limit(process_id.get(), rttime_usec)
+    }
+
+    /// Returns an `RLIMIT_RTTIME` budget for a thread whose callbacks each take up to
+    /// `callback_usec` microseconds of CPU time, i.e. `callback_usec * margin`, clamped to
+    /// [`rttime_usec_max()`](Self::rttime_usec_max).
+    ///
+    /// The limit is on the CPU time a real-time thread may use without blocking, so a budget of a
+    /// few callbacks' worth leaves room for occasional overruns, while still catching a runaway
+    /// thread much sooner than the daemon's maximum would. The result may be passed to
+    /// [`make_thread_realtime_with_rttime()`](Self::make_thread_realtime_with_rttime) as is, as it
+    /// never exceeds the daemon's ceiling. A negative or NaN `margin` gives a budget of 0.
+    pub fn recommended_rttime_usec(
+        &self,
+        callback_usec: u64,
+        margin: f64,
+    ) -> Result<u64, RtkitError> {
+        let rttime_max = self.rttime_usec_max()?.max(0) as u64;
+
+        // Float to integer casts saturate, so this cannot overflow
+        let budget = (callback_usec as f64 * margin) as u64;
+
+        Ok(budget.min(rttime_max))
+    }
+
+    /// Returns the range of real-time priorities that may be requested, i.e. from 1 to
+    /// [`max_realtime_priority_u32()`](Self::max_realtime_priority_u32), for example to populate
+    /// a settings dialog.
+    ///
+    /// Note that 0 is not a valid real-time priority, and so is not included. The range is empty
+    /// if the daemon does not permit real-time scheduling at all.
+    pub fn realtime_priority_range(&self) -> Result<RangeInclusive<u32>, RtkitError> {
+        Ok(1..=self.max_realtime_priority_u32()?)
+    }
+
+    /// Returns the range of nice levels that may be requested, i.e. from
+    /// [`min_nice_level()`](Self::min_nice_level) to 0.
+    pub fn nice_level_range(&self) -> Result<RangeInclusive<i32>, RtkitError> {
+        Ok(self.min_nice_level()?..=0)
+    }
+
+    /// Returns the real-time priority that a thread would get if `requested` were asked for and
+    /// clamped to the maximum permitted by the daemon (see
+    /// [`max_realtime_priority()`](Self::max_realtime_priority)), without changing the scheduling
+    /// of any thread, for example to show the effective value next to a settings slider.
+    ///
+    /// This only reads the daemon's maximum (from the cache, if property caching is enabled).
+    pub fn preview_granted_priority(&self, requested: u32) -> Result<u32, RtkitError> {
+        let max = self.max_realtime_priority()?.max(0) as u32;
+
+        Ok(requested.min(max))
+    }
+
+    /// Clamps the `requested` real-time priority to the maximum permitted by the daemon (see
+    /// [`max_realtime_priority()`](Self::max_realtime_priority)).
+    ///
+    /// This is the same computation as
+    /// [`preview_granted_priority()`](Self::preview_granted_priority), for use when preparing a
+    /// request rather than previewing one.
+    pub fn clamp_realtime_priority(&self, requested: u32) -> Result<u32, RtkitError> {
+        self.preview_granted_priority(requested)
+    }
+
+    /// Clamps the `requested` nice level to the minimum permitted by the daemon (see
+    /// [`min_nice_level()`](Self::min_nice_level)).
+    pub fn clamp_nice_level(&self, requested: i32) -> Result<i32, RtkitError> {
+        let min = self.min_nice_level()?;
+
+        Ok(requested.max(min))
+    }
+
+    /// Requests a nice level of `priority` for the specified thread id (this is a non-real-time
+    /// scheduling level).
+    #[must_use = "the request may have failed, leaving the thread's scheduling unchanged"]
+    pub fn make_thread_high_priority(
+        &self,
+        thread_id: impl Into<ThreadId>,
+        priority: impl Into<NiceLevel>,
+    ) -> Result<(), RtkitError> {
+        let thread_id = thread_id.into();
+        let priority = priority.into();
+
+        self.call(&proxy::make_thread_high_priority(
+            thread_id.get(),
+            priority.get(),
+        ))?;
+
+        Ok(())
+    }
+
+    /// Requests a nice level of `priority` for the specified thread id of a specified process id
+    /// (this is a non-real-time scheduling level).
+    #[must_use = "the request may have failed, leaving the thread's scheduling unchanged"]
+    pub fn make_thread_high_priority_with_pid(
+        &self,
+        process_id: impl Into<ProcessId>,
+        thread_id: impl Into<ThreadId>,
+        priority: impl Into<NiceLevel>,
+    ) -> Result<(), RtkitError> {
+        let process_id = process_id.into();
+        let thread_id = thread_id.into();
+        let priority = priority.into();
+
+        self.call(&proxy::make_thread_high_priority_with_pid(
+            process_id.get(),
+            thread_id.get(),
+            priority.get(),
+        ))?;
+
+        Ok(())
+    }
+
+    /// Like [`make_thread_high_priority()`](Self::make_thread_high_priority), but first checks
+    /// that `priority` is within the range permitted by the daemon, i.e. between
+    /// [`min_nice_level()`](Self::min_nice_level) and 0, returning
+    /// [`RtkitError::NiceLevelOutOfRange`] if it is not. It also checks that the thread exists in
+    /// the current process, returning [`RtkitError::NoSuchThread`] if it does not.
+    ///
+    /// Note that _lower_ (more negative) nice levels correspond to _higher_ priority. Also note
+    /// that the thread may still exit after it has been checked, before the daemon acts on the
+    /// request.
+    #[must_use = "the request may have failed, leaving the thread's scheduling unchanged"]
+    pub fn make_thread_high_priority_checked(
+        &self,
+        thread_id: impl Into<ThreadId>,
+        priority: impl Into<NiceLevel>,
+    ) -> Result<(), RtkitError> {
+        let thread_id = thread_id.into();
+        let priority = priority.into();
+
+        check_thread_exists(None, thread_id)?;
+        self.check_nice_level(priority)?;
+
+        self.make_thread_high_priority(thread_id, priority)
+    }
+
+    /// Like [`make_thread_high_priority_with_pid()`](Self::make_thread_high_priority_with_pid),
+    /// but first checks that `priority` is within the range permitted by the daemon, and that the
+    /// thread exists in the specified process. See
+    /// [`make_thread_high_priority_checked()`](Self::make_thread_high_priority_checked) for
+    /// details.
+    #[must_use = "the request may have failed, leaving the thread's scheduling unchanged"]
+    pub fn make_thread_high_priority_with_pid_checked(
+        &self,
+        process_id: impl Into<ProcessId>,
+        thread_id: impl Into<ThreadId>,
+        priority: impl Into<NiceLevel>,
+    ) -> Result<(), RtkitError> {
+        let process_id = process_id.into();
+        let thread_id = thread_id.into();
+        let priority = priority.into();
+
+        check_thread_exists(Some(process_id), thread_id)?;
+        self.check_nice_level(priority)?;
+
+        self.make_thread_high_priority_with_pid(process_id, thread_id, priority)
+    }
+
+    /// Like [`make_thread_high_priority_checked()`](Self::make_thread_high_priority_checked), but
+    /// also checks that `priority` is allowed by the current process's `RLIMIT_NICE` (see
+    /// [`rlimit_min_nice_level()`](Self::rlimit_min_nice_level)), returning
+    /// [`RtkitError::NiceLevelExceedsRlimit`] if it is not.
+    ///
+    /// This is for configurations where the granted nice level is bounded by `RLIMIT_NICE`, so
+    /// that exceeding it gives a clear error rather than a denial from the daemon. Note that
+    /// many systems default to an `RLIMIT_NICE` of 0, which does not allow any nice level below
+    /// 0.
+    #[must_use = "the request may have failed, leaving the thread's scheduling unchanged"]
+    pub fn make_thread_high_priority_rlimit_checked(
+        &self,
+        thread_id: impl Into<ThreadId>,
+        priority: impl Into<NiceLevel>,
+    ) -> Result<(), RtkitError> {
+        let priority = priority.into();
+
+        if let Some(min) = Self::rlimit_min_nice_level()?
+            && priority.get() < min
+        {
+            return Err(RtkitError::NiceLevelExceedsRlimit {
+                requested: priority.get(),
+                min,
+            });
+        }
+
+        self.make_thread_high_priority_checked(thread_id, priority)
+    }
+
+    /// Returns the lowest nice level (i.e. the highest priority) that the current process's
+    /// `RLIMIT_NICE` allows a thread to be given, or `None` if it is unlimited.
+    ///
+    /// The kernel uses a `20 - limit` convention for `RLIMIT_NICE`, since resource limits cannot
+    /// be negative. A limit of 20 allows a nice level of 0, a limit of 40 allows the lowest
+    /// possible nice level of -20, and so on.
+    pub fn rlimit_min_nice_level() -> Result<Option<i32>, RtkitError> {
+        Ok(sys::nice_limit()?.map(min_nice_level_for_rlimit))
+    }
+
+    fn check_nice_level(&self, priority: NiceLevel) -> Result<(), RtkitError> {
+        NiceLevel::new(priority.get(), self.min_nice_level()?)?;
+
+        Ok(())
+    }
+
+    /// Requests a real-time priority of `priority` for the specified thread id.
+    ///
+    /// The daemon always grants real-time scheduling using the `SCHED_RR` policy (see
+    /// [`realtime_policy()`](Self::realtime_policy)), with `SCHED_RESET_ON_FORK` set so that child
+    /// processes do not inherit it.
+    #[must_use = "the request may have failed, leaving the thread's scheduling unchanged"]
+    pub fn make_thread_realtime(
+        &self,
+        thread_id: impl Into<ThreadId>,
+        priority: impl Into<RealtimePriority>,
+    ) -> Result<(), RtkitError> {
+        let thread_id = thread_id.into();
+        let priority = priority.into();
+
+        self.call(&proxy::make_thread_realtime(
+            thread_id.get(),
+            priority.get(),
+        ))?;
+
+        Ok(())
+    }
+
+    /// Like [`make_thread_realtime()`](Self::make_thread_realtime), but afterwards reads back the
+    /// thread's scheduling parameters to confirm that it has a real-time policy with the requested
+    /// priority, returning [`RtkitError::PromotionNotApplied`] if it does not.
+    ///
+    /// This guards against the daemon reporting success without the change having been applied
+    /// (or the thread having been reset in the meantime).
+    #[must_use = "the request may have failed, leaving the thread's scheduling unchanged"]
+    pub fn make_thread_realtime_verified(
+        &self,
+        thread_id: impl Into<ThreadId>,
+        priority: impl Into<RealtimePriority>,
+    ) -> Result<(), RtkitError> {
+        let thread_id = thread_id.into();
+        let priority = priority.into();
+
+        self.make_thread_realtime(thread_id, priority)?;
+
+        verify_realtime(thread_id, priority)
+    }
+
+    /// Changes the real-time priority of a thread that is already real-time to `new_priority`,
+    /// e.g. to lower it from 20 to 10 without going back to `SCHED_OTHER` in between, and then
+    /// confirms the change as with
+    /// [`make_thread_realtime_verified()`](Self::make_thread_realtime_verified).
+    ///
+    /// The daemon treats a repeated request like any other: it applies the new priority (higher or
+    /// lower) if it is within [`max_realtime_priority()`](Self::max_realtime_priority), and the
+    /// usual checks, such as on `RLIMIT_RTTIME`, pass. It may still refuse, for example if too many
+    /// requests have been made recently (see [`RtkitError::UserThreadLimitReached`]), as it limits
+    /// their rate. If it refuses a request to lower the priority (i.e. `new_priority` is at or
+    /// below the thread's current priority), and the process has `CAP_SYS_NICE` (see
+    /// [`has_sys_nice_capability()`](Self::has_sys_nice_capability)), the priority is set directly
+    /// instead, as with [`make_thread_realtime_direct()`](Self::make_thread_realtime_direct).
+    /// Refusals to raise the priority are always returned, so that the daemon's limits still
+    /// apply.
+    #[must_use = "the request may have failed, leaving the thread's scheduling unchanged"]
+    pub fn adjust_thread_realtime_priority(
+        &self,
+        thread_id: impl Into<ThreadId>,
+        new_priority: impl Into<RealtimePriority>,
+    ) -> Result<(), RtkitError> {
+        let thread_id = thread_id.into();
+        let new_priority = new_priority.into();
+
+        if let Err(err) = self.make_thread_realtime(thread_id, new_priority) {
+            if !matches!(
+                err,
+                RtkitError::PermissionDenied { .. } | RtkitError::UserThreadLimitReached { .. }
+            ) || new_priority.get() > sys::sched_getattr(thread_id.get())?.priority
+                || !Self::has_sys_nice_capability()?
+            {
+                return Err(err);
+            }
+
+            Self::make_thread_realtime_direct(thread_id, new_priority)?;
+        }
+
+        verify_realtime(thread_id, new_priority)
+    }
+
+    /// Like [`make_thread_realtime()`](Self::make_thread_realtime), but afterwards reads back the
+    /// thread's scheduling parameters, and returns the real-time priority that it actually has.
+    ///
+    /// This may differ from the requested `priority`, e.g. if the daemon's policy grants a lower
+    /// one, or the thread was changed again in the meantime. A thread without a real-time policy
+    /// reports a priority of 0.
+    #[must_use = "the granted priority may differ from the one requested"]
+    pub fn make_thread_realtime_returning(
+        &self,
+        thread_id: impl Into<ThreadId>,
+        priority: impl Into<RealtimePriority>,
+    ) -> Result<u32, RtkitError> {
+        let thread_id = thread_id.into();
+
+        self.make_thread_realtime(thread_id, priority)?;
+
+        Ok(sys::sched_getattr(thread_id.get())?.priority)
+    }
+
+    /// Requests a real-time priority of `priority` for the specified thread id, and then pins it to
+    /// the given `cpus` with `sched_setaffinity()`, as is common for low-latency threads (such as
+    /// audio callbacks) to avoid the jitter of migrating between CPUs.
+    ///
+    /// The thread must exist for its affinity to be changed. Changing the affinity of a thread of
+    /// another process also requires having the same user id as it, or the `CAP_SYS_NICE`
+    /// capability. If the affinity cannot be set, the thread is left real-time, and an error is
+    /// returned (an empty `cpus`, or one without any online CPUs, fails with
+    /// [`RtkitError::Io`]).
+    #[must_use = "the request may have failed, leaving the thread's scheduling unchanged"]
+    pub fn make_thread_realtime_pinned(
+        &self,
+        thread_id: impl Into<ThreadId>,
+        priority: impl Into<RealtimePriority>,
+        cpus: &[usize],
+    ) -> Result<(), RtkitError> {
+        let thread_id = thread_id.into();
+
+        // Fail before the promotion, rather than leaving a thread real-time but not pinned
+        sys::check_thread_id(thread_id.get())?;
+
+        self.make_thread_realtime(thread_id, priority)?;
+
+        sys::set_affinity(thread_id.get(), cpus)
+    }
+
+    /// Requests a real-time priority of `requested` for the specified thread id, and then reads
+    /// back the thread's scheduling parameters to report what it was actually granted, e.g. for
+    /// metrics.
+    ///
+    /// `rtkit` itself refuses requests above
+    /// [`max_realtime_priority()`](Self::max_realtime_priority) rather than lowering them, so
+    /// [`PromotionReport::clamped`] is only set if something else changed the thread's priority,
+    /// such as another implementation of the daemon's interface.
+    ///
+    /// If the thread does not have a real-time policy afterwards, this returns
+    /// [`RtkitError::PromotionNotApplied`] rather than a report.
+    #[must_use = "the request may have failed, leaving the thread's scheduling unchanged"]
+    pub fn promotion_report(
+        &self,
+        thread_id: impl Into<ThreadId>,
+        requested: u32,
+    ) -> Result<PromotionReport, RtkitError> {
+        let thread_id = thread_id.into();
+
+        self.make_thread_realtime(thread_id, requested)?;
+
+        let attr = sys::sched_getattr(thread_id.get())?;
+        let granted_policy = SchedPolicy::try_from(attr.policy)?;
+
+        if !granted_policy.is_realtime() {
+            return Err(RtkitError::PromotionNotApplied {
+                thread_id: thread_id.get(),
+                policy: granted_policy,
+                priority: attr.priority,
+            });
+        }
+
+        Ok(PromotionReport {
+            requested,
+            granted_policy,
+            granted_priority: attr.priority,
+            clamped: attr.priority < requested,
+        })
+    }
+
+    /// Requests a real-time priority of `priority` for each of the specified thread ids.
+    ///
+    /// Rather than stopping at the first failure, this makes a request for every thread and
+    /// returns the result for each one, as some threads might, for example, have exited.
+    #[must_use = "each thread's request may have failed independently"]
+    pub fn make_threads_realtime(
+        &self,
+        thread_ids: &[ThreadId],
+        priority: impl Into<RealtimePriority>,
+    ) -> ThreadResults {
+        let priority = priority.into();
+
+        thread_ids
+            .iter()
+            .map(|&thread_id| (thread_id, self.make_thread_realtime(thread_id, priority)))
+            .collect()
+    }
+
+    /// Like [`make_thread_realtime()`](Self::make_thread_realtime), but first checks that
+    /// `RLIMIT_RTTIME` has been set for the current process to a value no higher than
+    /// [`rttime_usec_max()`](Self::rttime_usec_max).
+    ///
+    /// The daemon refuses real-time requests from processes without a suitable `RLIMIT_RTTIME`,
+    /// but this is reported as a generic permission error, indistinguishable from being denied by
+    /// PolicyKit. Performing this check first returns [`RtkitError::RttimeLimitNotSet`] instead,
+    /// so that the two causes can be told apart.
+    ///
+    /// It also checks that the thread exists in the current process, returning
+    /// [`RtkitError::NoSuchThread`] if it does not. The thread may still exit after this check,
+    /// before the daemon acts on the request.
+    #[must_use = "the request may have failed, leaving the thread's scheduling unchanged"]
+    pub fn make_thread_realtime_checked(
+        &self,
+        thread_id: impl Into<ThreadId>,
+        priority: impl Into<RealtimePriority>,
+    ) -> Result<(), RtkitError> {
+        let thread_id = thread_id.into();
+        let priority = priority.into();
+
+        check_thread_exists(None, thread_id)?;
+
+        let max = self.rttime_usec_max()?.max(0) as u64;
+
+        match sys::rttime_limit()? {
+            Some(limit) if limit <= max => (),
+            limit => return Err(RtkitError::RttimeLimitNotSet { limit, max }),
+        }
+
+        self.make_thread_realtime(thread_id, priority)
+    }
+
+    /// Sets `RLIMIT_RTTIME` for the current process to `rttime_usec` microseconds, and then
+    /// requests a real-time priority of `priority` for the specified thread id.
+    ///
+    /// If `rttime_usec` exceeds [`rttime_usec_max()`](Self::rttime_usec_max), it is clamped to
+    /// that maximum if `clamp` is `true`, or an error is returned otherwise.
+    ///
+    /// Note that `RLIMIT_RTTIME` is a per-process limit, so this affects all threads of the
+    /// process.
+    #[must_use = "the request may have failed, leaving the thread's scheduling unchanged"]
+    pub fn make_thread_realtime_with_rttime(
+        &self,
+        thread_id: impl Into<ThreadId>,
+        priority: impl Into<RealtimePriority>,
+        rttime_usec: u64,
+        clamp: bool,
+    ) -> Result<(), RtkitError> {
+        let thread_id = thread_id.into();
+        let priority = priority.into();
+
+        let rttime_max = self.rttime_usec_max()?.max(0) as u64;
+
+        let rttime_usec = if rttime_usec <= rttime_max {
+            rttime_usec
+        } else if clamp {
+            rttime_max
+        } else {
+            return Err(RtkitError::RttimeExceedsMax {
+                requested: rttime_usec,
+                max: rttime_max,
+            });
+        };
+
+        sys::set_rttime_limit(rttime_usec)?;
+
+        self.make_thread_realtime(thread_id, priority)
+    }
+
+    /// Sets `RLIMIT_RTTIME` for the current process to a budget of `budget_usec` microseconds, and
+    /// then requests a real-time priority of `priority` for the specified thread id, returning
+    /// [`RtkitError::RttimeExceedsMax`] if the budget is higher than
+    /// [`rttime_usec_max()`](Self::rttime_usec_max).
+    ///
+    /// This is the same as
+    /// [`make_thread_realtime_with_rttime()`](Self::make_thread_realtime_with_rttime) without
+    /// clamping. Note that the kernel measures each thread's CPU time against the limit
+    /// separately, but the limit itself is shared by every thread in the process, on all kernel
+    /// versions. Giving different threads different budgets is therefore not possible: each call
+    /// replaces the budget for all of them, so the most recent one applies.
+    #[must_use = "the request may have failed, leaving the thread's scheduling unchanged"]
+    pub fn make_thread_realtime_budgeted(
+        &self,
+        thread_id: impl Into<ThreadId>,
+        priority: impl Into<RealtimePriority>,
+        budget_usec: u64,
+    ) -> Result<(), RtkitError> {
+        self.make_thread_realtime_with_rttime(thread_id, priority, budget_usec, false)
+    }
+
+    /// Requests a real-time priority of `priority` for the specified thread id of a specified
+    /// process id.
+    ///
+    /// The daemon requires the target process (rather than the caller) to have a suitable
+    /// `RLIMIT_RTTIME`, which may be set with [`prlimit_rttime()`](Self::prlimit_rttime).
+    #[must_use = "the request may have failed, leaving the thread's scheduling unchanged"]
+    pub fn make_thread_realtime_with_pid(
+        &self,
+        process_id: impl Into<ProcessId>,
+        thread_id: impl Into<ThreadId>,
+        priority: impl Into<RealtimePriority>,
+    ) -> Result<(), RtkitError> {
+        let process_id = process_id.into();
+        let thread_id = thread_id.into();
+        let priority = priority.into();
+
+        self.call(&proxy::make_thread_realtime_with_pid(
+            process_id.get(),
+            thread_id.get(),
+            priority.get(),
+        ))?;
+
+        Ok(())
+    }
+
+    /// Requests a real-time priority of `priority` for every thread of the specified process,
+    /// listed in `/proc/<pid>/task`, via
+    /// [`make_thread_realtime_with_pid()`](Self::make_thread_realtime_with_pid). This is useful
+    /// for supervisors that promote a child process after starting it.
+    ///
+    /// As with [`make_threads_realtime()`](Self::make_threads_realtime), the result for each
+    /// thread is returned, rather than stopping at the first failure. Threads that exit while this
+    /// is in progress are left out of the results. An error is only returned if the process's
+    /// threads cannot be listed at all, e.g. because it does not exist.
+    #[must_use = "each thread's request may have failed independently"]
+    pub fn make_process_threads_realtime(
+        &self,
+        process_id: impl Into<ProcessId>,
+        priority: impl Into<RealtimePriority>,
+    ) -> Result<ThreadResults, RtkitError> {
+        let process_id = process_id.into();
+        let priority = priority.into();
+
+        let task_dir = format!("/proc/{process_id}/task");
+        let mut results = Vec::new();
+
+        for thread_id in sys::task_ids(&task_dir)? {
+            let result = self.make_thread_realtime_with_pid(process_id, thread_id, priority);
+
+            // The request probably failed because the thread has since exited
+            if result.is_err() && !sys::task_exists(&task_dir, thread_id).unwrap_or(false) {
+                continue;
+            }
+
+            results.push((thread_id.into(), result));
+        }
+
+        Ok(results)
+    }
+
+    /// Requests a nice level of `nice` for the calling thread (this is a non-real-time scheduling
+    /// level).
+    #[must_use = "the request may have failed, leaving the thread's scheduling unchanged"]
+    pub fn make_current_thread_high_priority(
+        &self,
+        nice: impl Into<NiceLevel>,
+    ) -> Result<(), RtkitError> {
+        self.make_thread_high_priority(Self::current_thread_id(), nice)
+    }
+
+    /// Requests a real-time priority of `priority` for the calling thread.
+    #[must_use = "the request may have failed, leaving the thread's scheduling unchanged"]
+    pub fn make_current_thread_realtime(
+        &self,
+        priority: impl Into<RealtimePriority>,
+    ) -> Result<(), RtkitError> {
+        self.make_thread_realtime(Self::current_thread_id(), priority)
+    }
+
+    /// Requests a real-time priority `below_max` levels below the maximum permitted by the daemon
+    /// for the calling thread, and returns the priority that was requested. For example, a
+    /// `below_max` of 1 requests one below the ceiling, without hardcoding its value.
+    ///
+    /// The priority is clamped to a minimum of 1, as 0 is not a valid real-time priority, so a
+    /// `below_max` at or above the maximum requests the lowest real-time priority rather than
+    /// failing.
+    #[must_use = "the request may have failed, leaving the thread's scheduling unchanged"]
+    pub fn make_current_thread_realtime_relative(&self, below_max: u32) -> Result<u32, RtkitError> {
+        let priority = self
+            .max_realtime_priority_u32()?
+            .saturating_sub(below_max)
+            .max(1);
+
+        self.make_current_thread_realtime(priority)?;
+
+        Ok(priority)
+    }
+
+    /// Requests a real-time priority of `priority` for the calling thread, unless it already has a
+    /// real-time scheduling policy, and reports which of the two happened.
+    ///
+    /// This suits startup paths that may run more than once, avoiding a redundant call to the
+    /// daemon. Note that [`PromotionOutcome::AlreadyRealtime`] only means that the thread's policy
+    /// is `SCHED_FIFO` or `SCHED_RR`, and does not check that its priority matches `priority`.
+    #[must_use = "the request may have failed, leaving the thread's scheduling unchanged"]
+    pub fn promote_current_thread(
+        &self,
+        priority: impl Into<RealtimePriority>,
+    ) -> Result<PromotionOutcome, RtkitError> {
+        let thread_id = Self::current_thread_id();
+
+        if Self::current_scheduling_policy(thread_id)?.is_realtime() {
+            return Ok(PromotionOutcome::AlreadyRealtime);
+        }
+
+        let priority = self.make_thread_realtime_returning(thread_id, priority)?;
+
+        Ok(PromotionOutcome::Promoted { priority })
+    }
+
+    /// Requests a real-time priority of `priority` for every thread in the current process named
+    /// `name` (for example, using `pthread_setname_np()` or [`std::thread::Builder::name()`]),
+    /// and returns the number of threads that were promoted.
+    ///
+    /// Thread names are matched against `/proc/self/task/<tid>/comm`, where the kernel truncates
+    /// them to 15 bytes. Returns [`RtkitError::NoThreadNamed`] if no thread matches, and stops at
+    /// the first thread that fails to be promoted.
+    #[must_use = "the request may have failed, leaving the thread's scheduling unchanged"]
+    pub fn make_named_thread_realtime(
+        &self,
+        name: &str,
+        priority: impl Into<RealtimePriority>,
+    ) -> Result<usize, RtkitError> {
+        let priority = priority.into();
+
+        let thread_ids = sys::task_ids_named("/proc/self/task", name)?;
+        if thread_ids.is_empty() {
+            return Err(RtkitError::NoThreadNamed {
+                name: name.to_string(),
+            });
+        }
+
+        for &thread_id in &thread_ids {
+            self.make_thread_realtime(thread_id, priority)?;
+        }
+
+        Ok(thread_ids.len())
+    }
+
+    /// Makes the calling thread real-time, taking care of the necessary setup. This:
+    ///
+    /// 1. Sets `RLIMIT_RTTIME` for the current process to the maximum permitted by the daemon
+    ///    (see [`apply_max_rttime_limit()`](Self::apply_max_rttime_limit)).
+    /// 2. Clamps `desired_priority` to the maximum permitted by the daemon (see
+    ///    [`clamp_realtime_priority()`](Self::clamp_realtime_priority)).
+    /// 3. Requests that priority for the calling thread.
+    ///
+    /// Only the calling thread is promoted, although the `RLIMIT_RTTIME` applies to the whole
+    /// process. If any step fails, [`RtkitError::RealtimeSetupFailed`] is returned, indicating
+    /// which step it was, along with the underlying error.
+    #[must_use = "the request may have failed, leaving the thread's scheduling unchanged"]
+    pub fn go_realtime(&self, desired_priority: u32) -> Result<(), RtkitError> {
+        let failed = |step| {
+            move |source| RtkitError::RealtimeSetupFailed {
+                step,
+                source: Box::new(source),
+            }
+        };
+
+        self.apply_max_rttime_limit()
+            .map_err(failed(RealtimeSetupStep::SetRttimeLimit))?;
+
+        let priority = self
+            .clamp_realtime_priority(desired_priority)
+            .map_err(failed(RealtimeSetupStep::ClampPriority))?;
+
+        self.make_current_thread_realtime(priority)
+            .map_err(failed(RealtimeSetupStep::Promote))
+    }
+
+    /// Requests a real-time priority of `priority` for the calling thread, returning a guard that
+    /// restores the thread's previous scheduling policy and priority when it is dropped.
+    ///
+    /// `RLIMIT_RTTIME` must be set before calling this, as with
+    /// [`make_thread_realtime()`](Self::make_thread_realtime).
+    pub fn promote_current_scoped(
+        &self,
+        priority: impl Into<RealtimePriority>,
+    ) -> Result<RealtimePromotion, RtkitError> {
+        let thread_id = Self::current_thread_id();
+        let previous = sys::sched_getattr(thread_id.get())?;
+
+        self.make_thread_realtime(thread_id, priority)?;
+
+        Ok(RealtimePromotion::new(thread_id, previous))
+    }
+
+    /// Requests a real-time priority of `priority` for the calling thread, returning a token that
+    /// holds its previous scheduling policy and priority, to be restored by passing it to
+    /// [`exit_realtime()`](Self::exit_realtime).
+    ///
+    /// This is an alternative to [`promote_current_scoped()`](Self::promote_current_scoped) with
+    /// explicit entry and exit points, e.g. around latency-critical work driven by a C event loop.
+    /// `RLIMIT_RTTIME` must be set before calling this, as with
+    /// [`make_thread_realtime()`](Self::make_thread_realtime).
+    pub fn enter_realtime(
+        &self,
+        priority: impl Into<RealtimePriority>,
+    ) -> Result<RealtimeToken, RtkitError> {
+        let thread_id = Self::current_thread_id();
+        let previous = sys::sched_getattr(thread_id.get())?;
+
+        self.make_thread_realtime(thread_id, priority)?;
+
+        Ok(RealtimeToken::new(thread_id, previous))
+    }
+
+    /// Restores the scheduling policy and priority that the thread had before the call to
+    /// [`enter_realtime()`](Self::enter_realtime) that returned `token`, directly via
+    /// `sched_setattr()`. This may be called from any thread.
+    pub fn exit_realtime(&self, token: RealtimeToken) -> Result<(), RtkitError> {
+        token.restore()
+    }
+
+    /// Resets all threads that `rtkit` has promoted (and is still tracking) back to non-real-time
+    /// scheduling.
+    pub fn reset_known(&self) -> Result<(), RtkitError> {
+        self.call(&proxy::reset_known())?;
+
+        Ok(())
+    }
+
+    /// Resets every real-time and high priority thread on the system back to non-real-time
+    /// scheduling.
+    ///
+    /// Note that this affects _all_ clients of `rtkit`, and not just the current process. This
+    /// usually requires elevated privileges, and may fail if PolicyKit denies the request.
+    pub fn reset_all(&self) -> Result<(), RtkitError> {
+        self.call(&proxy::reset_all())?;
+
+        Ok(())
+    }
+
+    /// Asks the `rtkit` daemon to terminate. This is mostly useful in test harnesses and other
+    /// controlled environments, where the daemon will be restarted (e.g. via D-Bus activation).
+    pub fn exit(&self) -> Result<(), RtkitError> {
+        self.call(&proxy::exit())?;
+
+        Ok(())
+    }
+
+    /// Calls `method` on the daemon's `org.freedesktop.RealtimeKit1` interface with the given
+    /// arguments, and returns the reply, so that methods added to the daemon can be used before
+    /// this crate wraps them. The call is made in the same way as for the wrapped methods, using
+    /// this `RTKit`'s service name, object path, timeout and retries.
+    ///
+    /// This is an unstable forward-compatibility escape hatch: neither the method name nor the
+    /// arguments are validated, and errors are reported as they are for the other methods.
+    pub fn call_raw<B>(&self, method: &str, body: &B) -> Result<Message, RtkitError>
+    where
+        B: Serialize + Type + std::fmt::Debug,
+    {
+        self.call(&proxy::raw(method, body))
+    }
+
+    /// Resets the specified thread to the default `SCHED_OTHER` policy with a nice level of 0,
+    /// directly via `sched_setattr()` rather than through the daemon.
+    ///
+    /// This allows threads to be demoted immediately, even those that the daemon isn't tracking
+    /// (whereas [`reset_known()`](Self::reset_known) only affects threads it is tracking). Note
+    /// that `rtkit` sets `SCHED_RESET_ON_FORK` on the threads it promotes, so that child processes
+    /// do not inherit real-time scheduling. That flag cannot be cleared by unprivileged threads,
+    /// so it is preserved.
+    pub fn reset_thread(thread_id: impl Into<ThreadId>) -> Result<(), RtkitError> {
+        let thread_id = thread_id.into();
+        sys::restore_sched_attr(thread_id.get(), &SchedAttr::default())
+    }
+
+    /// Sets the nice level of the specified thread to `nice`, directly via `setpriority()` rather
+    /// than through the daemon. Lowering the nice level (i.e. raising the priority) requires the
+    /// `CAP_SYS_NICE` capability, or a sufficient `RLIMIT_NICE` (see
+    /// [`rlimit_min_nice_level()`](Self::rlimit_min_nice_level)), but does not need `rtkit` to be
+    /// running.
+    ///
+    /// If the process is not permitted to do this, [`RtkitError::DirectPromotionDenied`] is
+    /// returned. This allows best-effort priority even without the daemon, by trying it first
+    /// and falling back to a direct change:
+    ///
+    /// ```no_run
+    /// use rtkit_rs::RTKit;
+    ///
+    /// # fn main() -> Result<(), rtkit_rs::RtkitError> {
+    /// let thread_id = RTKit::current_thread_id();
+    ///
+    /// let result = match RTKit::try_new() {
+    ///     Some(rtkit) => rtkit.make_thread_high_priority(thread_id, -10),
+    ///     None => RTKit::make_thread_high_priority_direct(thread_id, -10),
+    /// };
+    /// # result
+    /// # }
+    /// ```
+    #[must_use = "the request may have failed, leaving the thread's scheduling unchanged"]
+    pub fn make_thread_high_priority_direct(
+        thread_id: impl Into<ThreadId>,
+        nice: impl Into<NiceLevel>,
+    ) -> Result<(), RtkitError> {
+        let thread_id = thread_id.into();
+
+        sys::set_thread_nice(thread_id.get(), nice.into().get())
+            .map_err(|err| direct_error(thread_id, err))
+    }
+
+    /// Makes the specified thread real-time with the given `priority`, directly via
+    /// `sched_setattr()` rather than through the daemon. This requires the `CAP_SYS_NICE`
+    /// capability (see [`has_sys_nice_capability()`](Self::has_sys_nice_capability)), or a
+    /// sufficient `RLIMIT_RTPRIO`, but does not need `rtkit` to be running.
+    ///
+    /// As with the daemon, the thread gets the [`SchedPolicy::Rr`] policy, with
+    /// `SCHED_RESET_ON_FORK` set so that child processes do not inherit it. No `RLIMIT_RTTIME` is
+    /// required, so a runaway thread will not be throttled unless the caller sets one.
+    ///
+    /// If the process is not permitted to do this, [`RtkitError::DirectPromotionDenied`] is
+    /// returned, and the caller can fall back to
+    /// [`make_thread_realtime()`](Self::make_thread_realtime).
+    #[must_use = "the request may have failed, leaving the thread's scheduling unchanged"]
+    pub fn make_thread_realtime_direct(
+        thread_id: impl Into<ThreadId>,
+        priority: impl Into<RealtimePriority>,
+    ) -> Result<(), RtkitError> {
+        let thread_id = thread_id.into();
+        let attr = SchedAttr {
+            policy: SchedPolicy::Rr.into(),
+            flags: sys::SCHED_FLAG_RESET_ON_FORK,
+            priority: priority.into().get(),
+            ..SchedAttr::default()
+        };
+
+        set_sched_attr_direct(thread_id, &attr)
+    }
+
+    /// Gives the specified thread the [`SchedPolicy::Deadline`] (`SCHED_DEADLINE`) policy,
+    /// directly via `sched_setattr()`. The thread is then guaranteed `runtime_ns` of CPU time
+    /// within `deadline_ns` of the start of every period of `period_ns` (all in nanoseconds). A
+    /// `period_ns` of 0 means the period is the same as the deadline.
+    ///
+    /// The `rtkit` daemon has no way to request deadline scheduling, so this always requires the
+    /// `CAP_SYS_NICE` capability (see
+    /// [`has_sys_nice_capability()`](Self::has_sys_nice_capability)), or fails with
+    /// [`RtkitError::DirectPromotionDenied`]. The kernel also checks that the new
+    /// bandwidth can be admitted alongside other deadline threads, and fails with
+    /// [`RtkitError::Io`] if not, or if the parameters are not such that
+    /// `runtime_ns <= deadline_ns <= period_ns`.
+    ///
+    /// As with real-time promotion, `SCHED_RESET_ON_FORK` is set, so that child processes do not
+    /// inherit the policy.
+    #[must_use = "the request may have failed, leaving the thread's scheduling unchanged"]
+    pub fn make_thread_deadline_direct(
+        thread_id: impl Into<ThreadId>,
+        runtime_ns: u64,
+        deadline_ns: u64,
+        period_ns: u64,
+    ) -> Result<(), RtkitError> {
+        let thread_id = thread_id.into();
+        let attr = SchedAttr {
+            policy: SchedPolicy::Deadline.into(),
+            flags: sys::SCHED_FLAG_RESET_ON_FORK,
+            runtime: runtime_ns,
+            deadline: deadline_ns,
+            period: period_ns,
+            ..SchedAttr::default()
+        };
+
+        set_sched_attr_direct(thread_id, &attr)
+    }
+
+    /// Resets the calling thread to the default `SCHED_OTHER` policy with a nice level of 0. See
+    /// [`reset_thread()`](Self::reset_thread) for details.
+    ///
+    /// This does not involve the daemon, so it works even if `rtkit` is no longer reachable.
+    pub fn demote_current_thread() -> Result<(), RtkitError> {
+        Self::reset_thread(Self::current_thread_id())
+    }
+
+    /// Returns whether the specified thread currently has a real-time scheduling policy (i.e.
+    /// `SCHED_FIFO` or `SCHED_RR`).
+    ///
+    /// Returns an error if the thread does not exist.
+    pub fn thread_is_realtime(thread_id: impl Into<ThreadId>) -> Result<bool, RtkitError> {
+        let thread_id = thread_id.into();
+        Ok(Self::current_scheduling_policy(thread_id)?.is_realtime())
+    }
+
+    /// Returns whether the specified thread, which was previously made real-time, has since been
+    /// demoted to a non-real-time policy (usually the default `SCHED_OTHER`).
+    ///
+    /// `rtkit` runs a canary thread to detect real-time threads starving the rest of the system.
+    /// If the canary fails to run, the daemon demotes every real-time thread it knows about,
+    /// without notifying the affected processes. Applications can call this periodically (or
+    /// when they notice that they are missing deadlines) to detect that, and either request
+    /// real-time scheduling again or back off. Note that a thread that exceeds its
+    /// `RLIMIT_RTTIME` is not demoted, but sent `SIGXCPU` (or `SIGKILL`, at the hard limit) by
+    /// the kernel.
+    ///
+    /// This only looks at the thread's current policy, so it is only meaningful for a thread that
+    /// was promoted. Use [`thread_lost_priority()`](Self::thread_lost_priority) to also detect a
+    /// real-time priority that has been lowered.
+    ///
+    /// Returns an error if the thread does not exist.
+    pub fn thread_was_demoted(thread_id: impl Into<ThreadId>) -> Result<bool, RtkitError> {
+        Ok(!Self::current_scheduling_policy(thread_id)?.is_realtime())
+    }
+
+    /// Returns whether the specified thread, which was previously granted a real-time priority of
+    /// `granted_priority`, no longer has it, i.e. it has been demoted (see
+    /// [`thread_was_demoted()`](Self::thread_was_demoted)), or its priority has been lowered.
+    ///
+    /// Returns an error if the thread does not exist.
+    pub fn thread_lost_priority(
+        thread_id: impl Into<ThreadId>,
+        granted_priority: impl Into<RealtimePriority>,
+    ) -> Result<bool, RtkitError> {
+        let thread_id = thread_id.into();
+        let attr = sys::sched_getattr(thread_id.get())?;
+
+        Ok(!SchedPolicy::try_from(attr.policy)?.is_realtime()
+            || attr.priority < granted_priority.into().get())
+    }
+
+    /// Returns the scheduling policy that the daemon assigns to threads that it makes real-time,
+    /// which is always [`SchedPolicy::Rr`] (`SCHED_RR`).
+    ///
+    /// Threads at the same priority therefore share the CPU in time slices, rather than running
+    /// until they block, as they would with `SCHED_FIFO`. Use
+    /// [`current_scheduling_policy()`](Self::current_scheduling_policy) to check the policy a
+    /// thread actually has.
+    pub fn realtime_policy() -> SchedPolicy {
+        SchedPolicy::Rr
+    }
+
+    /// Returns the scheduling policy that the specified thread currently has.
+    ///
+    /// Returns an error if the thread does not exist.
+    pub fn current_scheduling_policy(
+        thread_id: impl Into<ThreadId>,
+    ) -> Result<SchedPolicy, RtkitError> {
+        let thread_id = thread_id.into();
+        let attr = sys::sched_getattr(thread_id.get())?;
+
+        SchedPolicy::try_from(attr.policy)
+    }
+
+    /// Returns the nice level that the specified thread currently has, for example to check
+    /// whether a previous [`make_thread_high_priority()`](Self::make_thread_high_priority) request
+    /// is still in effect.
+    ///
+    /// Returns an error if the thread does not exist.
+    pub fn current_nice_level(thread_id: impl Into<ThreadId>) -> Result<i32, RtkitError> {
+        let thread_id = thread_id.into();
+        Ok(sys::sched_getattr(thread_id.get())?.nice)
+    }
+
+    /// Returns whether the specified thread has `SCHED_RESET_ON_FORK` set, so that any child
+    /// process it forks starts with the default scheduling rather than inheriting its real-time
+    /// policy.
+    ///
+    /// `rtkit` sets this flag on every thread it promotes, as do the `*_direct()` methods, so this
+    /// is mainly useful to check threads that were made real-time by other means, before forking.
+    ///
+    /// Returns an error if the thread does not exist.
+    pub fn current_scheduling_has_reset_on_fork(
+        thread_id: impl Into<ThreadId>,
+    ) -> Result<bool, RtkitError> {
+        let thread_id = thread_id.into();
+        let attr = sys::sched_getattr(thread_id.get())?;
+
+        Ok(attr.flags & sys::SCHED_FLAG_RESET_ON_FORK != 0)
+    }
+
+    /// Returns whether the current process has the `CAP_SYS_NICE` capability in its effective set,
+    /// as listed in `/proc/self/status`.
+    ///
+    /// A process with `CAP_SYS_NICE` (e.g. one running as root, or a service granted it via
+    /// `AmbientCapabilities=`) can set real-time scheduling directly with `sched_setattr(2)`,
+    /// without the overhead of a D-Bus round trip, or depending on PolicyKit and `rtkit` being
+    /// available. The recommended pattern is to check this first, and only fall back to `rtkit`
+    /// if the capability is missing.
+    pub fn has_sys_nice_capability() -> Result<bool, RtkitError> {
+        sys::has_capability(sys::CAP_SYS_NICE)
+    }
+
+    /// A convenience method to return the calling thread's thread id.
+    ///
+    /// A [`JoinHandle`](std::thread::JoinHandle) does not expose the kernel thread id that the
+    /// daemon needs, so to promote another thread, have it send its id back when it starts (or
+    /// use [`spawn_with_thread_id()`](Self::spawn_with_thread_id), which does this):
+    ///
+    /// ```no_run
+    /// use rtkit_rs::RTKit;
+    ///
+    /// # fn main() -> Result<(), rtkit_rs::RtkitError> {
+    /// let rtkit = RTKit::new()?;
+    /// rtkit.apply_max_rttime_limit()?;
+    ///
+    /// let (sender, receiver) = std::sync::mpsc::channel();
+    /// let handle = std::thread::spawn(move || {
+    ///     sender.send(RTKit::current_thread_id()).unwrap();
+    ///     // ... real-time work ...
+    /// });
+    ///
+    /// rtkit.make_thread_realtime(receiver.recv().unwrap(), 10)?;
+    /// handle.join().unwrap();
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// This cannot fail: on Linux, `gettid()` always succeeds, and if the raw syscall (used where
+    /// libc has no wrapper) nevertheless reports an error, the process id is returned instead of
+    /// an invalid thread id. On platforms other than Linux, this returns the process id as a
+    /// best-effort value.
+    pub fn current_thread_id() -> ThreadId {
+        sys::gettid().into()
+    }
+
+    /// Spawns a thread using `builder`, like [`std::thread::Builder::spawn()`], and returns its
+    /// thread id along with its handle, so that it can be promoted with
+    /// [`make_thread_realtime()`](Self::make_thread_realtime) and related methods.
+    ///
+    /// This waits for the new thread to report its id (see
+    /// [`current_thread_id()`](Self::current_thread_id)) before returning. The thread has not
+    /// started running `f` by then, but may do so at any time afterwards, so it may initially run
+    /// with its default scheduling.
+    pub fn spawn_with_thread_id<F, T>(
+        builder: std::thread::Builder,
+        f: F,
+    ) -> Result<(std::thread::JoinHandle<T>, ThreadId), RtkitError>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let (sender, receiver) = std::sync::mpsc::sync_channel(1);
+
+        let handle = builder.spawn(move || {
+            // The receiver is only dropped once we have sent this
+            let _ = sender.send(Self::current_thread_id());
+            f()
+        })?;
+
+        let thread_id = receiver
+            .recv()
+            .expect("spawned thread exited without sending its id");
+
+        Ok((handle, thread_id))
+    }
+
+    /// Returns the thread ids of all the threads in the current process, read from
+    /// `/proc/self/task`.
+    ///
+    /// Threads may exit at any time, so some of the returned threads may no longer exist by the
+    /// time the caller acts on them.
+    pub fn current_process_thread_ids() -> Result<Vec<ThreadId>, RtkitError> {
+        let thread_ids = sys::task_ids("/proc/self/task")?;
+
+        Ok(thread_ids.into_iter().map(ThreadId::from).collect())
+    }
+
+    /// A convenience method to return the current process id.
+    pub fn current_process_id() -> ProcessId {
+        (std::process::id() as u64).into()
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+
+    fn get_sched_attr() -> Result<libc::sched_attr, RtkitError> {
+        unsafe {
+            let mut attr: libc::sched_attr = std::mem::MaybeUninit::zeroed().assume_init();
+
+            let ret = libc::syscall(
+                libc::SYS_sched_getattr,
+                0,
+                &mut attr as *mut libc::sched_attr,
+                std::mem::size_of::<libc::sched_attr>(),
+                0,
+            );
+
+            if ret < 0 {
+                Err(std::io::Error::last_os_error().into())
+            } else {
+                Ok(attr)
+            }
+        }
+    }
+
+    struct FakeBus {
+        names: std::sync::Mutex<Vec<String>>,
+        activatable: Vec<String>,
+        deny_list_names: bool,
+    }
+
+    #[zbus::interface(name = "org.freedesktop.DBus")]
+    impl FakeBus {
+        fn list_names(&self) -> zbus::fdo::Result<Vec<String>> {
+            if self.deny_list_names {
+                return Err(zbus::fdo::Error::AccessDenied("denied".to_string()));
+            }
+
+            Ok(self.names.lock().unwrap().clone())
+        }
+
+        fn start_service_by_name(&self, name: String, _flags: u32) -> zbus::fdo::Result<u32> {
+            if !self.activatable.contains(&name) {
+                return Err(zbus::fdo::Error::ServiceUnknown(name));
+            }
+
+            self.names.lock().unwrap().push(name);
+
+            Ok(1)
+        }
+
+        // Never answers in time, for testing timeouts on calls to the bus itself
+        async fn get_name_owner(&self, _name: String) -> String {
+            async_io::Timer::after(Duration::from_secs(2)).await;
+
+            ":1.0".to_string()
+        }
+    }
+
+    struct FakeRtkit;
+
+    #[zbus::interface(name = "org.freedesktop.RealtimeKit1")]
+    impl FakeRtkit {
+        #[zbus(property, name = "MaxRealtimePriority")]
+        fn max_realtime_priority(&self) -> i32 {
+            20
+        }
+    }
+
+    // Returns a peer-to-peer connection to a bus that does not have rtkit registered (but on which
+    // the `activatable` services can be started), along with the server end of the connection
+    // (which must be kept alive for the duration of the test).
+    fn fake_bus_connection(activatable: &[&str]) -> (Connection, Connection) {
+        let bus = FakeBus {
+            names: std::sync::Mutex::new(vec!["org.freedesktop.DBus".to_string()]),
+            activatable: activatable.iter().map(|s| s.to_string()).collect(),
+            deny_list_names: false,
+        };
+
+        serve_fake_bus(bus, false)
+    }
+
+    // Like `fake_bus_connection()`, but for a bus that does not allow `ListNames`, on which the
+    // rtkit object may be reachable nonetheless
+    fn restricted_bus_connection(with_rtkit: bool) -> (Connection, Connection) {
+        let bus = FakeBus {
+            names: std::sync::Mutex::new(Vec::new()),
+            activatable: Vec::new(),
+            deny_list_names: true,
+        };
+
+        serve_fake_bus(bus, with_rtkit)
+    }
+
+    fn serve_fake_bus(bus: FakeBus, with_rtkit: bool) -> (Connection, Connection) {
+        let (client, server) = std::os::unix::net::UnixStream::pair().unwrap();
+
+        let server = std::thread::spawn(move || {
+            let mut builder = zbus::blocking::connection::Builder::unix_stream(server)
+                .server(zbus::Guid::generate())
+                .unwrap()
+                .p2p()
+                .serve_at("/org/freedesktop/DBus", bus)
+                .unwrap();
+
+            if with_rtkit {
+                builder = builder.serve_at(RTKIT_OBJECT_PATH, FakeRtkit).unwrap();
+            }
+
+            builder.build().unwrap()
+        });
+
+        let client = zbus::blocking::connection::Builder::unix_stream(client)
+            .p2p()
+            .build()
+            .unwrap();
+
+        (client, server.join().unwrap())
+    }
+
+    #[test]
+    fn test_is_available() {
+        assert!(RTKit::is_available().unwrap());
+    }
+
+    #[test]
+    fn test_daemon_unavailable() {
+        let (connection, _server) = fake_bus_connection(&[]);
+
+        assert!(matches!(
+            RTKit::from_connection(connection),
+            Err(RtkitError::DaemonUnavailable)
+        ));
+    }
+
+    #[test]
+    fn test_builder() {
+        let rtkit = RTKit::builder().system_bus().build().unwrap();
+        assert_eq!(rtkit.max_realtime_priority().unwrap(), 20);
+
+        assert!(matches!(
+            RTKit::builder()
+                .service_name("org.example.NotRealtimeKit")
+                .build(),
+            Err(RtkitError::DaemonUnavailable)
+        ));
+
+        // Falls back to the session bus, where the service isn't available either
+        assert!(
+            RTKit::builder()
+                .service_name("org.example.NotRealtimeKit")
+                .session_bus_fallback(true)
+                .build()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_properties() {
+        let rtkit = RTKit::new().unwrap();
+        let properties = rtkit.properties().unwrap();
+
+        assert_eq!(properties.max_realtime_priority, 20);
+        assert_eq!(properties.min_nice_level, -15);
+        assert_eq!(properties.rttime_usec_max, 200000);
+    }
+
+    #[test]
+    fn test_property_cache() {
+        let mut rtkit = RTKit::new_cached().unwrap();
+
+        for _ in 0..2 {
+            assert_eq!(rtkit.max_realtime_priority().unwrap(), 20);
+            assert_eq!(rtkit.min_nice_level().unwrap(), -15);
+            assert_eq!(rtkit.rttime_usec_max().unwrap(), 200000);
+        }
+
+        rtkit.invalidate_cache();
+        assert_eq!(rtkit.max_realtime_priority().unwrap(), 20);
+    }
+
+    #[test]
+    fn test_clone() {
+        let rtkit = RTKit::new().unwrap();
+        let clone = rtkit.clone();
+
+        let thread = std::thread::spawn(move || clone.max_realtime_priority().unwrap());
+
+        assert_eq!(rtkit.max_realtime_priority().unwrap(), 20);
+        assert_eq!(thread.join().unwrap(), 20);
+    }
+
+    #[test]
+    fn test_connection() {
+        // The fake bus can "start" rtkit, which is enough to construct an `RTKit`
+        let (connection, _server) = fake_bus_connection(&[RTKIT_SERVICE_NAME]);
+        let config = Config {
+            auto_start: true,
+            ..Config::default()
+        };
+        let rtkit = RTKit::from_parts(connection, config).unwrap();
+
+        assert!(
+            is_rtkit_available(
+                rtkit.connection().unwrap(),
+                RTKIT_SERVICE_NAME,
+                RTKIT_OBJECT_PATH
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_try_from_connection() {
+        let (connection, _server) = fake_bus_connection(&[]);
+        assert!(matches!(
+            RTKit::try_from(connection),
+            Err(RtkitError::DaemonUnavailable)
+        ));
+
+        let (connection, _server) = restricted_bus_connection(true);
+        assert!(RTKit::try_from(connection).is_ok());
+    }
+
+    #[test]
+    fn test_check_available() {
+        let (connection, _server) = fake_bus_connection(&[RTKIT_SERVICE_NAME]);
+        let rtkit = RTKit::from_shared(SharedConnection::new(connection), Config::default());
+        assert!(!rtkit.check_available().unwrap());
+
+        // The daemon is started later, and found on the same connection
+        let connection = rtkit.connection().unwrap();
+        ensure_rtkit_available(connection, RTKIT_SERVICE_NAME, RTKIT_OBJECT_PATH, true).unwrap();
+        assert!(rtkit.check_available().unwrap());
+    }
+
+    #[test]
+    fn test_debug() {
+        let (connection, _server) = fake_bus_connection(&[RTKIT_SERVICE_NAME]);
+        let config = Config {
+            auto_start: true,
+            ..Config::default()
+        };
+        let rtkit = RTKit::from_parts(connection, config).unwrap();
+
+        assert_eq!(
+            format!("{rtkit:?}"),
+            "RTKit { service: \"org.freedesktop.RealtimeKit1\", .. }"
+        );
+    }
+
+    #[test]
+    fn test_list_names_denied() {
+        let (connection, _server) = restricted_bus_connection(true);
+        assert!(is_rtkit_available(&connection, RTKIT_SERVICE_NAME, RTKIT_OBJECT_PATH).unwrap());
+
+        let (connection, _server) = restricted_bus_connection(false);
+        assert!(!is_rtkit_available(&connection, RTKIT_SERVICE_NAME, RTKIT_OBJECT_PATH).unwrap());
+    }
+
+    #[cfg(feature = "test-util")]
+    #[test]
+    fn test_limits_summary() {
+        let mock = MockRtkit::with_properties(10, -5, 100000).unwrap();
+        let rtkit = RTKit::from_connection(mock.connection()).unwrap();
+
+        assert_eq!(
+            rtkit.limits_summary().unwrap(),
+            "rtkit: max_rt_prio=10, min_nice=-5, rttime_max=100000us"
+        );
+    }
+
+    #[cfg(feature = "test-util")]
+    #[test]
+    fn test_refresh() {
+        let mock = MockRtkit::new().unwrap();
+        let config = Config {
+            cache_properties: true,
+            ..Config::default()
+        };
+        let mut rtkit = RTKit::from_parts(mock.connection(), config).unwrap();
+
+        assert_eq!(rtkit.max_realtime_priority().unwrap(), 20);
+        assert!(!rtkit.refresh().unwrap());
+
+        // Simulate the daemon being restarted with a different configuration
+        let restarted = MockRtkit::with_properties(10, -15, 200000).unwrap();
+        rtkit.connection = Arc::new(SharedConnection::new(restarted.connection()));
+
+        assert_eq!(rtkit.max_realtime_priority().unwrap(), 20);
+        assert!(rtkit.refresh().unwrap());
+        assert_eq!(rtkit.max_realtime_priority().unwrap(), 10);
+        assert!(!rtkit.refresh().unwrap());
+
+        // Without caching, nothing is compared
+        let mut rtkit = RTKit::from_connection(mock.connection()).unwrap();
+        assert!(!rtkit.refresh().unwrap());
+    }
+
+    #[cfg(feature = "test-util")]
+    #[test]
+    fn test_with_timeout() {
+        let mock = MockRtkit::new().unwrap();
+        let rtkit = RTKit::from_connection(mock.connection()).unwrap();
+
+        let view = rtkit.with_timeout(Duration::from_secs(5));
+        assert_eq!(view.config.timeout, Some(Duration::from_secs(5)));
+        assert_eq!(view.max_realtime_priority().unwrap(), 20);
+        view.reset_all().unwrap();
+
+        // The original is unaffected
+        assert_eq!(rtkit.config.timeout, None);
+        assert_eq!(mock.requests(), vec![MockRequest::ResetAll]);
+    }
+
+    #[cfg(feature = "test-util")]
+    #[test]
+    fn test_make_current_thread_realtime_relative() {
+        let mock = MockRtkit::new().unwrap();
+        let rtkit = RTKit::from_connection(mock.connection()).unwrap();
+        let thread_id = RTKit::current_thread_id().get();
+
+        assert_eq!(rtkit.make_current_thread_realtime_relative(1).unwrap(), 19);
+        assert_eq!(rtkit.make_current_thread_realtime_relative(0).unwrap(), 20);
+        // Clamped to the lowest valid priority
+        assert_eq!(rtkit.make_current_thread_realtime_relative(50).unwrap(), 1);
+
+        let requested: Vec<_> = mock
+            .requests()
+            .into_iter()
+            .map(|request| match request {
+                MockRequest::Realtime {
+                    process_id: None,
+                    thread_id: id,
+                    priority,
+                } if id == thread_id => priority,
+                request => panic!("unexpected request {request:?}"),
+            })
+            .collect();
+        assert_eq!(requested, vec![19, 20, 1]);
+    }
+
+    #[cfg(feature = "test-util")]
+    #[test]
+    fn test_promote_current_thread() {
+        let mock = MockRtkit::new().unwrap();
+        let rtkit = RTKit::from_connection(mock.connection()).unwrap();
+
+        std::thread::spawn(move || {
+            // The mock does not change the thread's scheduling
+            assert_eq!(
+                rtkit.promote_current_thread(10).unwrap(),
+                PromotionOutcome::Promoted { priority: 0 }
+            );
+            assert_eq!(mock.requests().len(), 1);
+
+            if RTKit::has_sys_nice_capability().unwrap() {
+                RTKit::make_thread_realtime_direct(RTKit::current_thread_id(), 5).unwrap();
+
+                assert_eq!(
+                    rtkit.promote_current_thread(10).unwrap(),
+                    PromotionOutcome::AlreadyRealtime
+                );
+                assert_eq!(mock.requests().len(), 1);
+            }
+        })
+        .join()
+        .unwrap();
+    }
+
+    #[cfg(feature = "test-util")]
+    #[test]
+    fn test_bus_kind() {
+        let mock = MockRtkit::new().unwrap();
+        let rtkit = RTKit::from_connection(mock.connection()).unwrap();
+        assert_eq!(rtkit.bus_kind(), BusKind::Existing);
+
+        let config = Config {
+            bus_kind: BusKind::Session,
+            ..Config::default()
+        };
+        let rtkit = RTKit::from_parts(mock.connection(), config).unwrap();
+        assert_eq!(
+            rtkit.with_timeout(Duration::from_secs(1)).bus_kind(),
+            BusKind::Session
+        );
+    }
+
+    #[cfg(feature = "test-util")]
+    #[test]
+    fn test_make_thread_realtime_budgeted() {
+        let mock = MockRtkit::new().unwrap();
+        let rtkit = RTKit::from_connection(mock.connection()).unwrap();
+
+        // The limit is checked before anything is changed
+        assert!(matches!(
+            rtkit.make_thread_realtime_budgeted(1, 10, 200001),
+            Err(RtkitError::RttimeExceedsMax {
+                requested: 200001,
+                max: 200000
+            })
+        ));
+        assert!(mock.requests().is_empty());
+    }
+
+    #[cfg(feature = "test-util")]
+    #[test]
+    fn test_adjust_thread_realtime_priority() {
+        let mock = MockRtkit::new().unwrap();
+        let rtkit = RTKit::from_connection(mock.connection()).unwrap();
+        let (thread_id, tx, handle) = spawn_named_thread("rtkit-adjust");
+
+        // The mock accepts the request, but does not apply it
+        assert!(matches!(
+            rtkit.adjust_thread_realtime_priority(thread_id, 10),
+            Err(RtkitError::PromotionNotApplied {
+                policy: SchedPolicy::Other,
+                ..
+            })
+        ));
+
+        // Refused by the mock, as it is above the maximum, and never set directly, as that would
+        // raise the priority
+        assert!(matches!(
+            rtkit.adjust_thread_realtime_priority(thread_id, 21),
+            Err(RtkitError::PermissionDenied { .. })
+        ));
+        assert_eq!(sys::sched_getattr(thread_id.get()).unwrap().priority, 0);
+
+        // Lowering a priority that the daemon refuses is done directly, given the capability
+        if RTKit::has_sys_nice_capability().unwrap() {
+            let mock = MockRtkit::with_properties(5, -15, 200000).unwrap();
+            let rtkit = RTKit::from_connection(mock.connection()).unwrap();
+
+            RTKit::make_thread_realtime_direct(thread_id, 15).unwrap();
+            rtkit
+                .adjust_thread_realtime_priority(thread_id, 10)
+                .unwrap();
+            assert_eq!(sys::sched_getattr(thread_id.get()).unwrap().priority, 10);
+
+            assert!(matches!(
+                rtkit.adjust_thread_realtime_priority(thread_id, 12),
+                Err(RtkitError::PermissionDenied { .. })
+            ));
+            assert_eq!(sys::sched_getattr(thread_id.get()).unwrap().priority, 10);
+        }
+
+        tx.send(()).unwrap();
+        handle.join().unwrap();
+    }
+
+    #[cfg(feature = "test-util")]
+    #[test]
+    fn test_lazy() {
+        let mock = Arc::new(MockRtkit::new().unwrap());
+        let attempts = Arc::new(std::sync::atomic::AtomicU32::new(0));
+
+        let connect = {
+            let mock = mock.clone();
+            let attempts = attempts.clone();
+
+            move || {
+                // The bus is not ready the first time
+                match attempts.fetch_add(1, std::sync::atomic::Ordering::SeqCst) {
+                    0 => Err(RtkitError::DaemonUnavailable),
+                    _ => Ok(mock.connection()),
+                }
+            }
+        };
+
+        let rtkit = RTKit::from_lazy(Config::default(), connect);
+        assert_eq!(attempts.load(std::sync::atomic::Ordering::SeqCst), 0);
+
+        assert!(matches!(
+            rtkit.max_realtime_priority(),
+            Err(RtkitError::DaemonUnavailable)
+        ));
+        assert_eq!(rtkit.max_realtime_priority().unwrap(), 20);
+
+        // The connection is kept, and shared with clones
+        rtkit.clone().reset_all().unwrap();
+        rtkit.connection().unwrap();
+        assert_eq!(attempts.load(std::sync::atomic::Ordering::SeqCst), 2);
+        assert_eq!(mock.requests(), vec![MockRequest::ResetAll]);
+    }
+
+    #[cfg(feature = "test-util")]
+    #[test]
+    fn test_promotion_report() {
+        let mock = MockRtkit::new().unwrap();
+        let rtkit = RTKit::from_connection(mock.connection()).unwrap();
+        let (thread_id, tx, handle) = spawn_named_thread("rtkit-report");
+
+        // The mock accepts the request without applying it
+        assert!(matches!(
+            rtkit.promotion_report(thread_id, 10),
+            Err(RtkitError::PromotionNotApplied {
+                policy: SchedPolicy::Other,
+                priority: 0,
+                ..
+            })
+        ));
+
+        // A thread that is left at a lower real-time priority was clamped
+        if RTKit::has_sys_nice_capability().unwrap() {
+            RTKit::make_thread_realtime_direct(thread_id, 5).unwrap();
+
+            assert_eq!(
+                rtkit.promotion_report(thread_id, 10).unwrap(),
+                PromotionReport {
+                    requested: 10,
+                    granted_policy: SchedPolicy::Rr,
+                    granted_priority: 5,
+                    clamped: true,
+                }
+            );
+        }
+
+        tx.send(()).unwrap();
+        handle.join().unwrap();
+    }
+
+    #[cfg(feature = "test-util")]
+    #[test]
+    fn test_make_thread_realtime_pinned() {
+        let mock = MockRtkit::new().unwrap();
+        let rtkit = RTKit::from_connection(mock.connection()).unwrap();
+        let (thread_id, tx, handle) = spawn_named_thread("rtkit-pinned");
+
+        let affinity = |thread_id: ThreadId| {
+            let mut set: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+            let ret = unsafe {
+                libc::sched_getaffinity(
+                    thread_id.get() as libc::pid_t,
+                    std::mem::size_of::<libc::cpu_set_t>(),
+                    &mut set,
+                )
+            };
+            assert_eq!(ret, 0);
+
+            (0..libc::CPU_SETSIZE as usize)
+                .filter(|&cpu| unsafe { libc::CPU_ISSET(cpu, &set) })
+                .collect::<Vec<_>>()
+        };
+
+        // Pin it to a CPU that the test is allowed to run on
+        let cpu = affinity(thread_id)[0];
+        rtkit
+            .make_thread_realtime_pinned(thread_id, 10, &[cpu])
+            .unwrap();
+        assert_eq!(affinity(thread_id), vec![cpu]);
+
+        assert!(matches!(
+            rtkit.make_thread_realtime_pinned(thread_id, 10, &[]),
+            Err(RtkitError::Io(_))
+        ));
+        assert!(matches!(
+            rtkit.make_thread_realtime_pinned(thread_id, 10, &[usize::MAX]),
+            Err(RtkitError::Io(e)) if e.kind() == std::io::ErrorKind::InvalidInput
+        ));
+
+        // An out-of-range id is rejected before anything is requested
+        let requests = mock.requests().len();
+        assert!(matches!(
+            rtkit.make_thread_realtime_pinned(1u64 << 32, 10, &[cpu]),
+            Err(RtkitError::NoSuchThread { .. })
+        ));
+        assert_eq!(mock.requests().len(), requests);
+
+        tx.send(()).unwrap();
+        handle.join().unwrap();
+    }
+
+    #[cfg(feature = "test-util")]
+    #[test]
+    fn test_enter_realtime() {
+        let mock = MockRtkit::new().unwrap();
+        let rtkit = RTKit::from_connection(mock.connection()).unwrap();
+
+        std::thread::spawn(move || {
+            let thread_id = RTKit::current_thread_id();
+            let token = rtkit.enter_realtime(10).unwrap();
+            assert_eq!(token.thread_id(), thread_id);
+            assert_eq!(mock.requests().len(), 1);
+
+            // The mock does not change the thread's scheduling, so do that here
+            if RTKit::has_sys_nice_capability().unwrap() {
+                RTKit::make_thread_realtime_direct(thread_id, 10).unwrap();
+            }
+
+            rtkit.exit_realtime(token).unwrap();
+            assert_eq!(
+                RTKit::current_scheduling_policy(thread_id).unwrap(),
+                SchedPolicy::Other
+            );
+        })
+        .join()
+        .unwrap();
+    }
+
+    #[test]
+    fn test_try_new() {
+        // Only fails where `new()` would
+        assert_eq!(RTKit::try_new().is_some(), RTKit::new().is_ok());
+    }
+
+    #[cfg(feature = "test-util")]
+    #[test]
+    fn test_interactive_authorization() {
+        let mock = MockRtkit::new().unwrap();
+        let config = Config {
+            interactive_authorization: true,
+            ..Config::default()
+        };
+        let rtkit = RTKit::from_parts(mock.connection(), config).unwrap();
+
+        // Replies, including errors, are still matched up with their calls
+        assert_eq!(rtkit.max_realtime_priority().unwrap(), 20);
+        rtkit.make_thread_realtime(1, 10).unwrap();
+        assert!(matches!(
+            rtkit.make_thread_realtime(1, 21),
+            Err(RtkitError::PermissionDenied { message: Some(_) })
+        ));
+        assert_eq!(
+            rtkit
+                .with_timeout(Duration::from_secs(5))
+                .min_nice_level()
+                .unwrap(),
+            -15
+        );
+
+        assert_eq!(
+            mock.requests(),
+            vec![MockRequest::Realtime {
+                process_id: None,
+                thread_id: 1,
+                priority: 10,
+            }]
+        );
+    }
+
+    #[cfg(feature = "test-util")]
+    #[test]
+    fn test_reset_known_on_drop() {
+        let mock = MockRtkit::new().unwrap();
+        let config = Config {
+            reset_known_on_drop: true,
+            ..Config::default()
+        };
+
+        let rtkit = RTKit::from_parts(mock.connection(), config).unwrap();
+        let clone = rtkit.clone();
+
+        // Nothing is reset until the last clone is dropped
+        drop(rtkit);
+        assert!(mock.requests().is_empty());
+
+        drop(clone);
+        assert_eq!(mock.requests(), vec![MockRequest::ResetKnown]);
+
+        // Nor is anything reset by default
+        drop(RTKit::from_connection(mock.connection()).unwrap());
+        assert_eq!(mock.requests().len(), 1);
+    }
+
+    #[test]
+    fn test_property_unsupported() {
+        // The fake daemon only exports MaxRealtimePriority
+        let (connection, _server) = restricted_bus_connection(true);
+        let rtkit = RTKit::from_connection(connection).unwrap();
+
+        assert_eq!(rtkit.max_realtime_priority().unwrap(), 20);
+        assert!(matches!(
+            rtkit.rttime_usec_max(),
+            Err(RtkitError::PropertyUnsupported {
+                name: "RTTimeUSecMax"
+            })
+        ));
+    }
+
+    #[test]
+    fn test_auto_start() {
+        let (connection, _server) = fake_bus_connection(&[RTKIT_SERVICE_NAME]);
+
+        assert!(
+            !ensure_rtkit_available(&connection, RTKIT_SERVICE_NAME, RTKIT_OBJECT_PATH, false)
+                .unwrap()
+        );
+        assert!(
+            ensure_rtkit_available(&connection, RTKIT_SERVICE_NAME, RTKIT_OBJECT_PATH, true)
+                .unwrap()
+        );
+        // Now that it has been started, it is available without auto-starting
+        assert!(
+            ensure_rtkit_available(&connection, RTKIT_SERVICE_NAME, RTKIT_OBJECT_PATH, false)
+                .unwrap()
+        );
+
+        let (connection, _server) = fake_bus_connection(&[]);
+        assert!(
+            !ensure_rtkit_available(&connection, RTKIT_SERVICE_NAME, RTKIT_OBJECT_PATH, true)
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_property() {
+        let rtkit = RTKit::new().unwrap();
+
+        // Test for default values
+        assert_eq!(rtkit.max_realtime_priority().unwrap(), 20);
+        assert_eq!(rtkit.max_realtime_priority_u32().unwrap(), 20);
+        assert_eq!(rtkit.min_nice_level().unwrap(), -15);
+        assert_eq!(rtkit.rttime_usec_max().unwrap(), 200000);
+    }
+
+    #[cfg(feature = "test-util")]
+    #[test]
+    fn test_property_matches_properties() {
+        // Each getter goes through `property()`, which should agree with a single `GetAll`
+        let mock = MockRtkit::with_properties(10, -5, 100000).unwrap();
+        let rtkit = RTKit::from_connection(mock.connection()).unwrap();
+
+        let properties = rtkit.properties().unwrap();
+        assert_eq!(
+            rtkit.property::<i32>("MaxRealtimePriority").unwrap(),
+            properties.max_realtime_priority
+        );
+        assert_eq!(
+            rtkit.property::<i32>("MinNiceLevel").unwrap(),
+            properties.min_nice_level
+        );
+        assert_eq!(
+            rtkit.property::<i64>("RTTimeUSecMax").unwrap(),
+            properties.rttime_usec_max
+        );
+
+        assert!(matches!(
+            rtkit.property::<u32>("MaxRealtimePriority"),
+            Err(RtkitError::UnexpectedVariant {
+                property: "MaxRealtimePriority",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_property_variant_mismatch() {
+        let value = Value::from(20u32);
+        let err = property_from_value::<i32>("MaxRealtimePriority", &value).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "unexpected D-Bus variant type for MaxRealtimePriority: expected i32"
+        );
+
+        let value = Value::from(200000i64);
+        assert_eq!(
+            property_from_value::<i64>("RTTimeUSecMax", &value).unwrap(),
+            200000
+        );
+    }
+
+    #[test]
+    fn test_clamp() {
+        let rtkit = RTKit::new().unwrap();
+
+        assert_eq!(rtkit.clamp_realtime_priority(10).unwrap(), 10);
+        assert_eq!(rtkit.clamp_realtime_priority(99).unwrap(), 20);
+        assert_eq!(rtkit.clamp_nice_level(-10).unwrap(), -10);
+        assert_eq!(rtkit.clamp_nice_level(-20).unwrap(), -15);
+    }
+
+    #[cfg(feature = "test-util")]
+    #[test]
+    fn test_preview_granted_priority() {
+        let mock = MockRtkit::with_properties(10, -5, 100000).unwrap();
+        let rtkit = RTKit::from_connection(mock.connection()).unwrap();
+
+        assert_eq!(rtkit.preview_granted_priority(5).unwrap(), 5);
+        assert_eq!(rtkit.preview_granted_priority(50).unwrap(), 10);
+        assert_eq!(rtkit.clamp_realtime_priority(50).unwrap(), 10);
+
+        // Nothing was requested from the daemon
+        assert!(mock.requests().is_empty());
+    }
+
+    #[test]
+    fn test_thread_id_retrieval() {
+        assert!(RTKit::current_thread_id().get() > 0);
+    }
+
+    // This checks whichever of the libc and rustix implementations is enabled, so it should be run
+    // both with and without the `rustix` feature
+    #[test]
+    fn test_thread_id_matches_syscall() {
+        let tid = unsafe { libc::syscall(libc::SYS_gettid) } as u64;
+        assert_eq!(RTKit::current_thread_id().get(), tid);
+    }
+
+    #[test]
+    fn test_current_process_thread_ids() {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let (done_sender, done_receiver) = std::sync::mpsc::channel::<()>();
+
+        let thread = std::thread::spawn(move || {
+            sender.send(RTKit::current_thread_id()).unwrap();
+            done_receiver.recv().unwrap();
+        });
+
+        let other_thread_id = receiver.recv().unwrap();
+        let thread_ids = RTKit::current_process_thread_ids().unwrap();
+
+        assert!(thread_ids.contains(&RTKit::current_thread_id()));
+        assert!(thread_ids.contains(&other_thread_id));
+        assert!(thread_ids.contains(&RTKit::current_process_id().get().into()));
+
+        done_sender.send(()).unwrap();
+        thread.join().unwrap();
+    }
+
+    // Spawns a thread called `name`, returning its thread id, and a sender which makes it exit
+    fn spawn_named_thread(
+        name: &str,
+    ) -> (
+        ThreadId,
+        std::sync::mpsc::Sender<()>,
+        std::thread::JoinHandle<()>,
+    ) {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let (done_sender, done_receiver) = std::sync::mpsc::channel::<()>();
+
+        let thread = std::thread::Builder::new()
+            .name(name.to_string())
+            .spawn(move || {
+                sender.send(RTKit::current_thread_id()).unwrap();
+                done_receiver.recv().unwrap();
+            })
+            .unwrap();
+
+        (receiver.recv().unwrap(), done_sender, thread)
+    }
+
+    #[test]
+    fn test_task_ids_named() {
+        let (thread_id, done_sender, thread) = spawn_named_thread("rtkit-named");
+
+        assert_eq!(
+            sys::task_ids_named("/proc/self/task", "rtkit-named").unwrap(),
+            vec![thread_id.get()]
+        );
+        assert!(
+            sys::task_ids_named("/proc/self/task", "rtkit-missing")
+                .unwrap()
+                .is_empty()
+        );
+
+        done_sender.send(()).unwrap();
+        thread.join().unwrap();
+    }
+
+    #[test]
+    fn test_check_thread_exists() {
+        let thread_id = RTKit::current_thread_id();
+        let process_id = RTKit::current_process_id();
+
+        assert!(check_thread_exists(None, thread_id).is_ok());
+        assert!(check_thread_exists(Some(process_id), thread_id).is_ok());
+
+        assert!(matches!(
+            check_thread_exists(None, 0x3fffffff.into()),
+            Err(RtkitError::NoSuchThread {
+                thread_id: 0x3fffffff
+            })
+        ));
+        assert!(matches!(
+            check_thread_exists(Some(0x3fffffff.into()), thread_id),
+            Err(RtkitError::NoSuchThread { .. })
+        ));
+    }
+
+    #[test]
+    fn test_daemon_info() {
+        let rtkit = RTKit::new().unwrap();
+        let info = rtkit.daemon_info().unwrap();
+
+        assert!(info.unique_name.starts_with(':'));
+        assert!(info.pid.is_some());
+    }
+
+    #[cfg(feature = "test-util")]
+    #[test]
+    fn test_recommended_rttime_usec() {
+        let mock = MockRtkit::with_properties(10, -5, 100000).unwrap();
+        let rtkit = RTKit::from_connection(mock.connection()).unwrap();
+
+        assert_eq!(rtkit.recommended_rttime_usec(5000, 4.0).unwrap(), 20000);
+        assert_eq!(rtkit.recommended_rttime_usec(5000, 1.5).unwrap(), 7500);
+        // Clamped to the daemon's maximum
+        assert_eq!(rtkit.recommended_rttime_usec(50000, 4.0).unwrap(), 100000);
+        assert_eq!(
+            rtkit.recommended_rttime_usec(u64::MAX, 2.0).unwrap(),
+            100000
+        );
+        assert_eq!(rtkit.recommended_rttime_usec(5000, -1.0).unwrap(), 0);
+        assert_eq!(rtkit.recommended_rttime_usec(5000, f64::NAN).unwrap(), 0);
+    }
+
+    #[cfg(feature = "test-util")]
+    #[test]
+    fn test_ranges() {
+        let mock = MockRtkit::with_properties(10, -5, 100000).unwrap();
+        let rtkit = RTKit::from_connection(mock.connection()).unwrap();
+
+        assert_eq!(rtkit.realtime_priority_range().unwrap(), 1..=10);
+        assert_eq!(rtkit.nice_level_range().unwrap(), -5..=0);
+
+        let mock = MockRtkit::with_properties(0, 0, 100000).unwrap();
+        let rtkit = RTKit::from_connection(mock.connection()).unwrap();
+
+        assert!(rtkit.realtime_priority_range().unwrap().is_empty());
+        assert_eq!(rtkit.nice_level_range().unwrap(), 0..=0);
+    }
+
+    #[cfg(feature = "test-util")]
+    #[test]
+    fn test_daemon_info_without_pid() {
+        let mock = MockRtkit::new().unwrap();
+        let rtkit = RTKit::from_connection(mock.connection()).unwrap();
+
+        let info = rtkit.daemon_info().unwrap();
+        assert_eq!(info.unique_name, ":1.0");
+        assert_eq!(info.pid, None);
+    }
+
+    #[test]
+    fn test_daemon_info_timeout() {
+        let (connection, _server) = restricted_bus_connection(true);
+        let rtkit = RTKit::from_connection(connection)
+            .unwrap()
+            .with_timeout(Duration::from_millis(100));
+
+        assert!(matches!(rtkit.daemon_info(), Err(RtkitError::Timeout)));
+    }
+
+    #[cfg(feature = "test-util")]
+    #[test]
+    fn test_implementation() {
+        // Like the reference daemon, the mock does not export a version
+        let mock = MockRtkit::new().unwrap();
+        let rtkit = RTKit::from_connection(mock.connection()).unwrap();
+
+        assert_eq!(rtkit.implementation().unwrap(), None);
+    }
+
+    #[cfg(feature = "test-util")]
+    #[test]
+    fn test_call_raw() {
+        let mock = MockRtkit::new().unwrap();
+        let rtkit = RTKit::from_connection(mock.connection()).unwrap();
+
+        rtkit
+            .call_raw("MakeThreadRealtime", &(1234u64, 10u32))
+            .unwrap();
+        assert_eq!(
+            mock.requests(),
+            vec![MockRequest::Realtime {
+                process_id: None,
+                thread_id: 1234,
+                priority: 10,
+            }]
+        );
+
+        assert!(matches!(
+            rtkit.call_raw("NoSuchMethod", &()),
+            Err(RtkitError::Daemon { name, .. }) if name == "org.freedesktop.DBus.Error.UnknownMethod"
+        ));
+    }
+
+    #[test]
+    fn test_prlimit_rttime() {
+        let mut child = std::process::Command::new("sleep")
+            .arg("10")
+            .spawn()
+            .unwrap();
+        let process_id = child.id() as u64;
+
+        RTKit::prlimit_rttime(process_id, 200000).unwrap();
+
+        let mut rlim = libc::rlimit {
+            rlim_cur: 0,
+            rlim_max: 0,
+        };
+        let ret = unsafe {
+            libc::prlimit(
+                process_id as libc::pid_t,
+                libc::RLIMIT_RTTIME,
+                std::ptr::null(),
+                &mut rlim,
+            )
+        };
+        assert_eq!(ret, 0);
+        assert_eq!(rlim.rlim_cur, 200000);
+        assert_eq!(rlim.rlim_max, 200000);
+
+        child.kill().unwrap();
+        child.wait().unwrap();
+
+        // Not truncated to 0, which would be the current process
+        assert!(matches!(
+            RTKit::prlimit_rttime(1u64 << 32, 200000),
+            Err(RtkitError::Io(e)) if e.raw_os_error() == Some(libc::EINVAL)
+        ));
+    }
+
+    #[test]
+    fn test_process_id_retrieval() {
+        assert!(RTKit::current_process_id().get() > 0);
+    }
+
+    #[test]
+    fn test_make_thread_high_priority() {
+        let rtkit = RTKit::new().unwrap();
+
+        let thread_id = RTKit::current_thread_id();
+        assert!(rtkit.make_thread_high_priority(thread_id, -10).is_ok());
+
+        let attr = get_sched_attr().unwrap();
+        assert_eq!(attr.sched_nice, -10);
+    }
+
+    #[test]
+    fn test_make_thread_high_priority_with_pid() {
+        let rtkit = RTKit::new().unwrap();
+
+        let pid = RTKit::current_process_id();
+        let thread_id = RTKit::current_thread_id();
+        rtkit
+            .make_thread_high_priority_with_pid(pid, thread_id, -10)
+            .unwrap();
+
+        let attr = get_sched_attr().unwrap();
+        assert_eq!(attr.sched_nice, -10);
+    }
+
+    #[test]
+    fn test_make_thread_high_priority_checked() {
+        let rtkit = RTKit::new().unwrap();
+        let thread_id = RTKit::current_thread_id();
+
+        assert!(matches!(
+            rtkit.make_thread_high_priority_checked(thread_id, -20),
+            Err(RtkitError::NiceLevelOutOfRange {
+                requested: -20,
+                min: -15
+            })
+        ));
+        assert!(matches!(
+            rtkit.make_thread_high_priority_checked(thread_id, 5),
+            Err(RtkitError::NiceLevelOutOfRange { .. })
+        ));
+
+        rtkit
+            .make_thread_high_priority_checked(thread_id, -10)
+            .unwrap();
+
+        let attr = get_sched_attr().unwrap();
+        assert_eq!(attr.sched_nice, -10);
+    }
+
+    #[cfg(feature = "test-util")]
+    #[test]
+    fn test_make_thread_high_priority_rlimit_checked() {
+        assert_eq!(min_nice_level_for_rlimit(25), -5);
+        assert_eq!(min_nice_level_for_rlimit(20), 0);
+        assert_eq!(min_nice_level_for_rlimit(0), 19);
+        assert_eq!(min_nice_level_for_rlimit(100), -20);
+
+        let mock = MockRtkit::new().unwrap();
+        let rtkit = RTKit::from_connection(mock.connection()).unwrap();
+        let thread_id = RTKit::current_thread_id();
+
+        // We can't raise the limit in the test, so check against whatever it is
+        match RTKit::rlimit_min_nice_level().unwrap() {
+            Some(min) if min > -15 => {
+                let requested = (min - 1).min(0);
+                assert!(matches!(
+                    rtkit.make_thread_high_priority_rlimit_checked(thread_id, requested),
+                    Err(RtkitError::NiceLevelExceedsRlimit { requested: r, min: m })
+                        if r == requested && m == min
+                ));
+                assert!(mock.requests().is_empty());
+            }
+            _ => {
+                rtkit
+                    .make_thread_high_priority_rlimit_checked(thread_id, -15)
+                    .unwrap();
+                assert_eq!(mock.requests().len(), 1);
+            }
+        }
+    }
+
+    #[test]
+    fn test_make_thread_realtime() {
+        let rtkit = RTKit::new().unwrap();
+        let rttime_max = rtkit.rttime_usec_max().unwrap() as u64;
+
+        let rlim = libc::rlimit {
+            rlim_cur: rttime_max,
+            rlim_max: rttime_max,
+        };
+
+        let ret = unsafe { libc::setrlimit(libc::RLIMIT_RTTIME, &rlim) };
+        assert_eq!(ret, 0);
+
+        let thread_id = RTKit::current_thread_id();
+        assert!(rtkit.make_thread_realtime(thread_id, 10).is_ok());
+
+        let attr = get_sched_attr().unwrap();
+        assert!(attr.sched_policy > libc::SCHED_OTHER as u32);
+        assert_eq!(attr.sched_priority, 10);
+        assert_eq!(
+            RTKit::current_scheduling_policy(thread_id).unwrap(),
+            RTKit::realtime_policy()
+        );
+    }
+
+    #[test]
+    fn test_make_thread_realtime_above_max() {
+        let rtkit = RTKit::new().unwrap();
+        rtkit.apply_max_rttime_limit().unwrap();
+
+        let max = rtkit.max_realtime_priority_u32().unwrap();
+        let err = rtkit
+            .make_thread_realtime(RTKit::current_thread_id(), max + 1)
+            .unwrap_err();
+
+        // The daemon's explanation is included in the error
+        match &err {
+            RtkitError::PermissionDenied {
+                message: Some(message),
+            }
+            | RtkitError::Daemon {
+                message: Some(message),
+                ..
+            } => assert!(err.to_string().contains(message.as_str())),
+            _ => panic!("unexpected error: {err}"),
+        }
+    }
+
+    #[test]
+    fn test_make_named_thread_realtime() {
+        let rtkit = RTKit::new().unwrap();
+        rtkit.apply_max_rttime_limit().unwrap();
 
-const RTKIT_OBJECT_PATH: &str = "/org/freedesktop/RealtimeKit1";
-const RTKIT_SERVICE_NAME: &str = "org.freedesktop.RealtimeKit1";
-const RTKIT_INTERFACE: &str = "org.freedesktop.RealtimeKit1";
+        let (thread_id, done_sender, thread) = spawn_named_thread("rtkit-audio");
 
-fn is_rtkit_available(connection: &Connection) -> Result<bool> {
-    let message = connection.call_method(
-        Some("org.freedesktop.DBus"),
-        "/org/freedesktop/DBus",
-        Some("org.freedesktop.DBus"),
-        "ListNames",
-        &(),
-    )?;
+        assert_eq!(
+            rtkit.make_named_thread_realtime("rtkit-audio", 10).unwrap(),
+            1
+        );
+        assert!(RTKit::thread_is_realtime(thread_id).unwrap());
 
-    let names: Vec<String> = message.body().deserialize()?;
+        assert!(matches!(
+            rtkit.make_named_thread_realtime("rtkit-missing", 10),
+            Err(RtkitError::NoThreadNamed { .. })
+        ));
 
-    Ok(names.contains(&"org.freedesktop.RealtimeKit1".to_string()))
-}
+        done_sender.send(()).unwrap();
+        thread.join().unwrap();
+    }
 
-/// The top-level structure providing access to the crate's functionality.
-pub struct RTKit {
-    connection: Connection,
-}
+    #[test]
+    fn test_make_thread_realtime_verified() {
+        let rtkit = RTKit::new().unwrap();
+        rtkit.apply_max_rttime_limit().unwrap();
 
-impl RTKit {
-    /// Create an instance of the `RTKit` structure. This makes a connection to the system D-Bus
-    /// daemon, and ensures that the `rtkit` daemon is available.
-    ///
-    /// Returns an `RTKit` structure if the connection succeeds and the daemon is available, or an
-    /// error otherwise.
-    pub fn new() -> anyhow::Result<RTKit> {
-        let connection = Connection::system()?;
+        let thread_id = RTKit::current_thread_id();
+        rtkit.make_thread_realtime_verified(thread_id, 10).unwrap();
+    }
+
+    #[cfg(feature = "test-util")]
+    #[test]
+    fn test_make_thread_realtime_verified_not_applied() {
+        // The mock accepts the request, but does not change the thread's scheduling
+        let mock = MockRtkit::new().unwrap();
+        let rtkit = RTKit::from_connection(mock.connection()).unwrap();
 
-        is_rtkit_available(&connection)?;
+        std::thread::spawn(move || {
+            let thread_id = RTKit::current_thread_id();
 
-        Ok(RTKit { connection })
+            assert!(matches!(
+                rtkit.make_thread_realtime_verified(thread_id, 10),
+                Err(RtkitError::PromotionNotApplied {
+                    policy: SchedPolicy::Other,
+                    priority: 0,
+                    ..
+                })
+            ));
+        })
+        .join()
+        .unwrap();
     }
 
-    /// Returns the maximum permitted real-time priority value.
-    pub fn max_realtime_priority(&self) -> anyhow::Result<i32> {
-        match self.connection.call_method(
-            Some(RTKIT_SERVICE_NAME),
-            RTKIT_OBJECT_PATH,
-            Some("org.freedesktop.DBus.Properties"),
-            "Get",
-            &("org.freedesktop.RealtimeKit1", "MaxRealtimePriority"),
-        ) {
-            Ok(message) => {
-                let body = message.body().clone().to_owned();
-                let variant: Result<Value> = body.deserialize();
-                match variant {
-                    Ok(value) => Ok(i32::try_from(&value).unwrap()),
-                    Err(e) => Err(e.into()),
-                }
-            }
-            Err(e) => Err(e.into()),
-        }
+    #[test]
+    fn test_make_thread_realtime_returning() {
+        let rtkit = RTKit::new().unwrap();
+        rtkit.apply_max_rttime_limit().unwrap();
+
+        let thread_id = RTKit::current_thread_id();
+        assert_eq!(
+            rtkit.make_thread_realtime_returning(thread_id, 10).unwrap(),
+            10
+        );
     }
 
-    /// Returns the minimum permitted nice level value.
-    pub fn min_nice_level(&self) -> anyhow::Result<i32> {
-        match self.connection.call_method(
-            Some(RTKIT_SERVICE_NAME),
-            RTKIT_OBJECT_PATH,
-            Some("org.freedesktop.DBus.Properties"),
-            "Get",
-            &("org.freedesktop.RealtimeKit1", "MinNiceLevel"),
-        ) {
-            Ok(message) => {
-                let body = message.body().clone().to_owned();
-                let variant: Result<Value> = body.deserialize();
-                match variant {
-                    Ok(value) => Ok(i32::try_from(&value).unwrap()),
-                    Err(e) => Err(e.into()),
-                }
-            }
-            Err(e) => Err(e.into()),
+    #[cfg(feature = "test-util")]
+    #[test]
+    fn test_make_thread_realtime_returning_not_applied() {
+        // As the mock does not change the thread's scheduling, no priority is granted
+        let mock = MockRtkit::new().unwrap();
+        let rtkit = RTKit::from_connection(mock.connection()).unwrap();
+
+        std::thread::spawn(move || {
+            let thread_id = RTKit::current_thread_id();
+
+            assert_eq!(
+                rtkit.make_thread_realtime_returning(thread_id, 10).unwrap(),
+                0
+            );
+        })
+        .join()
+        .unwrap();
+    }
+
+    #[test]
+    fn test_has_sys_nice_capability() {
+        let status = "Name:\tcat\nCapInh:\t0000000000000000\nCapEff:\t0000000000800000\n";
+        assert_eq!(sys::parse_effective_capabilities(status), Some(1 << 23));
+        assert_eq!(sys::parse_effective_capabilities("Name:\tcat\n"), None);
+
+        // We can't know what the test runs with, but it should always be readable
+        RTKit::has_sys_nice_capability().unwrap();
+    }
+
+    #[test]
+    fn test_make_thread_realtime_direct() {
+        let (thread_id, tx, handle) = spawn_named_thread("rtkit-direct");
+        assert!(!RTKit::current_scheduling_has_reset_on_fork(thread_id).unwrap());
+
+        let result = RTKit::make_thread_realtime_direct(thread_id, 10);
+        if RTKit::has_sys_nice_capability().unwrap() {
+            result.unwrap();
+            assert_eq!(
+                RTKit::current_scheduling_policy(thread_id).unwrap(),
+                SchedPolicy::Rr
+            );
+            assert_eq!(sys::sched_getattr(thread_id.get()).unwrap().priority, 10);
+            assert!(RTKit::current_scheduling_has_reset_on_fork(thread_id).unwrap());
+        } else {
+            assert!(matches!(
+                result,
+                Err(RtkitError::DirectPromotionDenied { thread_id: t }) if t == thread_id.get()
+            ));
         }
+
+        tx.send(()).unwrap();
+        handle.join().unwrap();
     }
 
-    /// Returns the maximum time (in microseconds) that may be set for `RLIMIT_RTTIME`. This is the
-    /// maximum time a real-time thread may continuously occupy the CPU before being blocked by a
-    /// system call.
-    ///
-    /// Applications _must_ set an `RTLIMIT_RTTIME` before attempting to request real-time
-    /// priority.
-    pub fn rttime_usec_max(&self) -> anyhow::Result<i64> {
-        match self.connection.call_method(
-            Some(RTKIT_SERVICE_NAME),
-            RTKIT_OBJECT_PATH,
-            Some("org.freedesktop.DBus.Properties"),
-            "Get",
-            &("org.freedesktop.RealtimeKit1", "RTTimeUSecMax"),
-        ) {
-            Ok(message) => {
-                let body = message.body().clone().to_owned();
-                let variant: Result<Value> = body.deserialize();
-                match variant {
-                    Ok(value) => Ok(i64::try_from(&value).unwrap()),
-                    Err(e) => Err(e.into()),
-                }
-            }
-            Err(e) => Err(e.into()),
+    #[test]
+    fn test_make_thread_high_priority_direct() {
+        let (thread_id, tx, handle) = spawn_named_thread("rtkit-renice");
+
+        // Raising the nice level is always permitted
+        RTKit::make_thread_high_priority_direct(thread_id, 5).unwrap();
+        assert_eq!(RTKit::current_nice_level(thread_id).unwrap(), 5);
+
+        let result = RTKit::make_thread_high_priority_direct(thread_id, -5);
+        if RTKit::has_sys_nice_capability().unwrap() {
+            result.unwrap();
+            assert_eq!(RTKit::current_nice_level(thread_id).unwrap(), -5);
+        } else if RTKit::rlimit_min_nice_level()
+            .unwrap()
+            .is_some_and(|min| min > -5)
+        {
+            assert!(matches!(
+                result,
+                Err(RtkitError::DirectPromotionDenied { thread_id: t }) if t == thread_id.get()
+            ));
         }
+
+        tx.send(()).unwrap();
+        handle.join().unwrap();
     }
 
-    /// Requests a nice level of `priority` for the specified thread id (this is a non-real-time
-    /// scheduling level).
-    pub fn make_thread_high_priority(&self, thread_id: u64, priority: i32) -> anyhow::Result<()> {
-        self.connection.call_method(
-            Some(RTKIT_SERVICE_NAME),
-            RTKIT_OBJECT_PATH,
-            Some(RTKIT_INTERFACE),
-            "MakeThreadHighPriority",
-            &(thread_id, priority),
-        )?;
+    #[test]
+    fn test_make_thread_deadline_direct() {
+        let (thread_id, tx, handle) = spawn_named_thread("rtkit-deadline");
 
-        Ok(())
+        // 1ms every 10ms
+        let result = RTKit::make_thread_deadline_direct(thread_id, 1_000_000, 10_000_000, 0);
+        if RTKit::has_sys_nice_capability().unwrap() {
+            result.unwrap();
+
+            let attr = sys::sched_getattr(thread_id.get()).unwrap();
+            assert_eq!(
+                RTKit::current_scheduling_policy(thread_id).unwrap(),
+                SchedPolicy::Deadline
+            );
+            assert_eq!(attr.runtime, 1_000_000);
+            assert_eq!(attr.deadline, 10_000_000);
+
+            // The runtime may not exceed the deadline
+            assert!(matches!(
+                RTKit::make_thread_deadline_direct(thread_id, 2_000_000, 1_000_000, 0),
+                Err(RtkitError::Io(_))
+            ));
+        } else {
+            assert!(matches!(
+                result,
+                Err(RtkitError::DirectPromotionDenied { .. })
+            ));
+        }
+
+        tx.send(()).unwrap();
+        handle.join().unwrap();
     }
 
-    /// Requests a nice level of `priority` for the specified thread id of a specified process id
-    /// (this is a non-real-time scheduling level).
-    pub fn make_thread_high_priority_with_pid(
-        &self,
-        process_id: u64,
-        thread_id: u64,
-        priority: i32,
-    ) -> anyhow::Result<()> {
-        self.connection.call_method(
-            Some(RTKIT_SERVICE_NAME),
-            RTKIT_OBJECT_PATH,
-            Some(RTKIT_INTERFACE),
-            "MakeThreadHighPriorityWithPID",
-            &(process_id, thread_id, priority),
-        )?;
+    #[cfg(feature = "test-util")]
+    #[test]
+    fn test_make_process_threads_realtime() {
+        let mock = MockRtkit::new().unwrap();
+        let rtkit = RTKit::from_connection(mock.connection()).unwrap();
 
-        Ok(())
+        let (thread_id, tx, handle) = spawn_named_thread("rtkit-process");
+        let process_id = RTKit::current_process_id();
+
+        let results = rtkit.make_process_threads_realtime(process_id, 10).unwrap();
+        assert!(results.iter().all(|(_, result)| result.is_ok()));
+        assert!(results.iter().any(|&(t, _)| t == thread_id));
+        assert!(mock.requests().contains(&MockRequest::Realtime {
+            process_id: Some(process_id.get()),
+            thread_id: thread_id.get(),
+            priority: 10,
+        }));
+
+        // Requests above the maximum fail for each thread
+        let results = rtkit.make_process_threads_realtime(process_id, 21).unwrap();
+        assert!(!results.is_empty());
+        assert!(results.iter().all(|(_, result)| result.is_err()));
+
+        assert!(rtkit.make_process_threads_realtime(0x3fffffff, 10).is_err());
+
+        tx.send(()).unwrap();
+        handle.join().unwrap();
     }
 
-    /// Requests a real-time priority of `priority` for the specified thread id.
-    pub fn make_thread_realtime(&self, thread_id: u64, priority: u32) -> anyhow::Result<()> {
-        self.connection.call_method(
-            Some(RTKIT_SERVICE_NAME),
-            RTKIT_OBJECT_PATH,
-            Some(RTKIT_INTERFACE),
-            "MakeThreadRealtime",
-            &(thread_id, priority),
-        )?;
+    #[test]
+    fn test_kernel_too_old() {
+        let err = std::io::Error::from_raw_os_error(libc::ENOSYS);
+        assert!(matches!(
+            sys::sched_attr_error(err),
+            RtkitError::KernelTooOld
+        ));
 
-        Ok(())
+        let err = std::io::Error::from_raw_os_error(libc::ESRCH);
+        assert!(matches!(sys::sched_attr_error(err), RtkitError::Io(_)));
     }
 
-    /// Requests a real-time priority of `priority` for the specified thread id of a specified
-    /// process id.
-    pub fn make_thread_realtime_with_pid(
-        &self,
-        process_id: u64,
-        thread_id: u64,
-        priority: u32,
-    ) -> anyhow::Result<()> {
-        self.connection.call_method(
-            Some(RTKIT_SERVICE_NAME),
-            RTKIT_OBJECT_PATH,
-            Some(RTKIT_INTERFACE),
-            "MakeThreadRealtimeWithPID",
-            &(process_id, thread_id, priority),
-        )?;
+    // Thread ids that don't fit in a `pid_t` must not be truncated, e.g. to 0, which the kernel
+    // takes to mean the calling thread
+    #[test]
+    fn test_thread_id_out_of_range() {
+        let thread_id = ThreadId::from(1u64 << 32);
+        let no_such_thread = |result: Result<_, RtkitError>| matches!(result, Err(RtkitError::NoSuchThread { thread_id: t }) if t == 1u64 << 32);
 
-        Ok(())
+        assert!(no_such_thread(
+            RTKit::thread_is_realtime(thread_id).map(drop)
+        ));
+        assert!(no_such_thread(RTKit::reset_thread(thread_id)));
+        assert!(no_such_thread(RTKit::make_thread_realtime_direct(
+            thread_id, 10
+        )));
+        assert!(no_such_thread(RTKit::make_thread_deadline_direct(
+            thread_id, 1_000_000, 10_000_000, 0
+        )));
+        assert!(no_such_thread(RTKit::make_thread_high_priority_direct(
+            thread_id, 5
+        )));
+
+        assert_eq!(
+            RTKit::current_scheduling_policy(RTKit::current_thread_id()).unwrap(),
+            SchedPolicy::Other
+        );
+        assert_eq!(
+            RTKit::current_nice_level(RTKit::current_thread_id()).unwrap(),
+            0
+        );
     }
 
-    /// A convenience method to return the calling thread's thread id.
-    pub fn current_thread_id() -> u64 {
-        unsafe { libc::syscall(libc::SYS_gettid) as u64 }
+    #[test]
+    fn test_thread_was_demoted() {
+        let (thread_id, tx, handle) = spawn_named_thread("rtkit-demoted");
+
+        if RTKit::has_sys_nice_capability().unwrap() {
+            RTKit::make_thread_realtime_direct(thread_id, 10).unwrap();
+            assert!(!RTKit::thread_was_demoted(thread_id).unwrap());
+
+            RTKit::reset_thread(thread_id).unwrap();
+            assert!(RTKit::thread_was_demoted(thread_id).unwrap());
+
+            // Any non-real-time policy counts, not just `SCHED_OTHER`
+            RTKit::make_thread_realtime_direct(thread_id, 10).unwrap();
+            let batch = SchedAttr {
+                policy: SchedPolicy::Batch.into(),
+                ..SchedAttr::default()
+            };
+            sys::sched_setattr(thread_id.get(), &batch).unwrap();
+            assert!(RTKit::thread_was_demoted(thread_id).unwrap());
+        }
+
+        tx.send(()).unwrap();
+        handle.join().unwrap();
+
+        assert!(RTKit::thread_was_demoted(0x3fffffff).is_err());
     }
 
-    /// A convenience method to return the current process id.
-    pub fn current_process_id() -> u64 {
-        std::process::id() as u64
+    #[test]
+    fn test_thread_lost_priority() {
+        let (thread_id, tx, handle) = spawn_named_thread("rtkit-lost");
+
+        if RTKit::has_sys_nice_capability().unwrap() {
+            RTKit::make_thread_realtime_direct(thread_id, 10).unwrap();
+            assert!(!RTKit::thread_lost_priority(thread_id, 10).unwrap());
+            assert!(!RTKit::thread_lost_priority(thread_id, 5).unwrap());
+            assert!(RTKit::thread_lost_priority(thread_id, 15).unwrap());
+
+            RTKit::reset_thread(thread_id).unwrap();
+            assert!(RTKit::thread_lost_priority(thread_id, 10).unwrap());
+        }
+
+        tx.send(()).unwrap();
+        handle.join().unwrap();
+
+        assert!(RTKit::thread_lost_priority(0x3fffffff, 10).is_err());
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[test]
+    fn test_spawn_with_thread_id() {
+        let (done_sender, done_receiver) = std::sync::mpsc::channel::<()>();
 
-    fn get_sched_attr() -> anyhow::Result<libc::sched_attr> {
-        unsafe {
-            let mut attr: libc::sched_attr = std::mem::MaybeUninit::zeroed().assume_init();
+        let builder = std::thread::Builder::new().name("rtkit-spawned".to_string());
+        let (handle, thread_id) = RTKit::spawn_with_thread_id(builder, move || {
+            done_receiver.recv().unwrap();
+            RTKit::current_thread_id()
+        })
+        .unwrap();
 
-            let ret = libc::syscall(
-                libc::SYS_sched_getattr,
-                0,
-                &mut attr as *mut libc::sched_attr,
-                std::mem::size_of::<libc::sched_attr>(),
-                0,
-            );
+        assert_eq!(
+            sys::task_ids_named("/proc/self/task", "rtkit-spawned").unwrap(),
+            vec![thread_id.get()]
+        );
 
-            if ret < 0 {
-                Err(std::io::Error::last_os_error().into())
-            } else {
-                Ok(attr)
-            }
-        }
+        done_sender.send(()).unwrap();
+        assert_eq!(handle.join().unwrap(), thread_id);
     }
 
     #[test]
-    fn test_property() {
+    fn test_go_realtime() {
         let rtkit = RTKit::new().unwrap();
 
-        // Test for default values
-        assert_eq!(rtkit.max_realtime_priority().unwrap(), 20);
-        assert_eq!(rtkit.min_nice_level().unwrap(), -15);
-        assert_eq!(rtkit.rttime_usec_max().unwrap(), 200000);
+        // Requesting more than the maximum is clamped rather than refused
+        rtkit.go_realtime(99).unwrap();
+
+        let attr = get_sched_attr().unwrap();
+        assert!(RTKit::thread_is_realtime(RTKit::current_thread_id()).unwrap());
+        assert_eq!(attr.sched_priority, 20);
     }
 
     #[test]
-    fn test_thread_id_retrieval() {
-        assert!(RTKit::current_thread_id() > 0);
+    fn test_make_thread_realtime_checked() {
+        let rtkit = RTKit::new().unwrap();
+        let thread_id = RTKit::current_thread_id();
+
+        let mut rlim = libc::rlimit {
+            rlim_cur: 0,
+            rlim_max: 0,
+        };
+        let ret = unsafe { libc::getrlimit(libc::RLIMIT_RTTIME, &mut rlim) };
+        assert_eq!(ret, 0);
+
+        if rlim.rlim_max == libc::RLIM_INFINITY {
+            assert!(matches!(
+                rtkit.make_thread_realtime_checked(thread_id, 10),
+                Err(RtkitError::RttimeLimitNotSet { limit: None, .. })
+            ));
+        }
+
+        rtkit.apply_max_rttime_limit().unwrap();
+        rtkit.make_thread_realtime_checked(thread_id, 10).unwrap();
+
+        let attr = get_sched_attr().unwrap();
+        assert_eq!(attr.sched_priority, 10);
     }
 
     #[test]
-    fn test_process_id_retrieval() {
-        assert!(RTKit::current_process_id() > 0);
+    fn test_make_threads_realtime() {
+        let rtkit = RTKit::new().unwrap();
+        rtkit.apply_max_rttime_limit().unwrap();
+
+        let thread_id = RTKit::current_thread_id();
+        // Larger than the kernel's maximum PID, so this thread cannot exist
+        let results = rtkit.make_threads_realtime(&[thread_id, 0x3fffffff.into()], 10);
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].0, thread_id);
+        assert!(results[0].1.is_ok());
+        assert_eq!(results[1].0.get(), 0x3fffffff);
+        assert!(results[1].1.is_err());
+
+        let attr = get_sched_attr().unwrap();
+        assert_eq!(attr.sched_priority, 10);
     }
 
     #[test]
-    fn test_make_thread_high_priority() {
+    fn test_make_thread_realtime_with_pid() {
         let rtkit = RTKit::new().unwrap();
+        let rttime_max = rtkit.rttime_usec_max().unwrap() as u64;
+
+        let rlim = libc::rlimit {
+            rlim_cur: rttime_max,
+            rlim_max: rttime_max,
+        };
+
+        let ret = unsafe { libc::setrlimit(libc::RLIMIT_RTTIME, &rlim) };
+        assert_eq!(ret, 0);
 
+        let process_id = RTKit::current_process_id();
         let thread_id = RTKit::current_thread_id();
-        assert!(rtkit.make_thread_high_priority(thread_id, -10).is_ok());
+        assert!(
+            rtkit
+                .make_thread_realtime_with_pid(process_id, thread_id, 10)
+                .is_ok()
+        );
 
         let attr = get_sched_attr().unwrap();
-        assert_eq!(attr.sched_nice, -10);
+        assert!(attr.sched_policy > libc::SCHED_OTHER as u32);
+        assert_eq!(attr.sched_priority, 10);
     }
 
     #[test]
-    fn test_make_thread_high_priority_with_pid() {
+    fn test_make_current_thread_high_priority() {
         let rtkit = RTKit::new().unwrap();
 
-        let pid = RTKit::current_process_id();
-        let thread_id = RTKit::current_thread_id();
-        rtkit
-            .make_thread_high_priority_with_pid(pid, thread_id, -10)
-            .unwrap();
+        rtkit.make_current_thread_high_priority(-10).unwrap();
 
         let attr = get_sched_attr().unwrap();
         assert_eq!(attr.sched_nice, -10);
     }
 
     #[test]
-    fn test_make_thread_realtime() {
+    fn test_make_current_thread_realtime() {
         let rtkit = RTKit::new().unwrap();
         let rttime_max = rtkit.rttime_usec_max().unwrap() as u64;
 
@@ -272,8 +3386,28 @@ mod tests {
         let ret = unsafe { libc::setrlimit(libc::RLIMIT_RTTIME, &rlim) };
         assert_eq!(ret, 0);
 
+        rtkit.make_current_thread_realtime(10).unwrap();
+
+        let attr = get_sched_attr().unwrap();
+        assert!(attr.sched_policy > libc::SCHED_OTHER as u32);
+        assert_eq!(attr.sched_priority, 10);
+    }
+
+    #[test]
+    fn test_make_thread_realtime_with_rttime() {
+        let rtkit = RTKit::new().unwrap();
+        let rttime_max = rtkit.rttime_usec_max().unwrap() as u64;
         let thread_id = RTKit::current_thread_id();
-        assert!(rtkit.make_thread_realtime(thread_id, 10).is_ok());
+
+        assert!(
+            rtkit
+                .make_thread_realtime_with_rttime(thread_id, 10, rttime_max + 1, false)
+                .is_err()
+        );
+
+        rtkit
+            .make_thread_realtime_with_rttime(thread_id, 10, rttime_max + 1, true)
+            .unwrap();
 
         let attr = get_sched_attr().unwrap();
         assert!(attr.sched_policy > libc::SCHED_OTHER as u32);
@@ -281,7 +3415,7 @@ mod tests {
     }
 
     #[test]
-    fn test_make_thread_realtime_with_pid() {
+    fn test_promote_current_scoped() {
         let rtkit = RTKit::new().unwrap();
         let rttime_max = rtkit.rttime_usec_max().unwrap() as u64;
 
@@ -293,14 +3427,146 @@ mod tests {
         let ret = unsafe { libc::setrlimit(libc::RLIMIT_RTTIME, &rlim) };
         assert_eq!(ret, 0);
 
-        let process_id = RTKit::current_process_id();
+        let before = get_sched_attr().unwrap();
+
+        {
+            let _guard = rtkit.promote_current_scoped(10).unwrap();
+
+            let attr = get_sched_attr().unwrap();
+            assert!(attr.sched_policy > libc::SCHED_OTHER as u32);
+            assert_eq!(attr.sched_priority, 10);
+        }
+
+        let after = get_sched_attr().unwrap();
+        assert_eq!(after.sched_policy, before.sched_policy);
+        assert_eq!(after.sched_priority, before.sched_priority);
+    }
+
+    #[test]
+    fn test_thread_is_realtime() {
+        let thread_id = RTKit::current_thread_id();
+        assert!(!RTKit::thread_is_realtime(thread_id).unwrap());
+
+        // Larger than the kernel's maximum PID, so this thread cannot exist
+        assert!(RTKit::thread_is_realtime(0x3fffffff).is_err());
+    }
+
+    #[test]
+    fn test_current_scheduling_policy() {
+        let thread_id = RTKit::current_thread_id();
+        assert_eq!(
+            RTKit::current_scheduling_policy(thread_id).unwrap(),
+            SchedPolicy::Other
+        );
+    }
+
+    #[test]
+    fn test_current_nice_level() {
+        // Use a separate thread, as unprivileged threads can't undo raising their nice level
+        std::thread::spawn(|| {
+            let thread_id = RTKit::current_thread_id();
+            let attr = sys::SchedAttr {
+                nice: 5,
+                ..Default::default()
+            };
+            sys::sched_setattr(thread_id.get(), &attr).unwrap();
+
+            assert_eq!(RTKit::current_nice_level(thread_id).unwrap(), 5);
+        })
+        .join()
+        .unwrap();
+
+        assert!(RTKit::current_nice_level(0x3fffffff).is_err());
+    }
+
+    #[test]
+    fn test_thread_is_realtime_after_promotion() {
+        let rtkit = RTKit::new().unwrap();
+        let thread_id = RTKit::current_thread_id();
+        let rttime_max = rtkit.rttime_usec_max().unwrap() as u64;
+
+        rtkit
+            .make_thread_realtime_with_rttime(thread_id, 10, rttime_max, false)
+            .unwrap();
+
+        assert!(RTKit::thread_is_realtime(thread_id).unwrap());
+    }
+
+    #[test]
+    fn test_apply_max_rttime_limit() {
+        let rtkit = RTKit::new().unwrap();
+
+        let applied = rtkit.apply_max_rttime_limit().unwrap();
+        assert_eq!(applied, rtkit.rttime_usec_max().unwrap() as u64);
+
+        let mut rlim = libc::rlimit {
+            rlim_cur: 0,
+            rlim_max: 0,
+        };
+        let ret = unsafe { libc::getrlimit(libc::RLIMIT_RTTIME, &mut rlim) };
+        assert_eq!(ret, 0);
+        assert_eq!(rlim.rlim_cur, applied);
+        assert_eq!(rlim.rlim_max, applied);
+    }
+
+    #[test]
+    fn test_reset_thread() {
         let thread_id = RTKit::current_thread_id();
-        assert!(rtkit
-            .make_thread_realtime_with_pid(process_id, thread_id, 10)
-            .is_ok());
+        RTKit::reset_thread(thread_id).unwrap();
+
+        let rtkit = RTKit::new().unwrap();
+        rtkit.apply_max_rttime_limit().unwrap();
+        rtkit.make_thread_realtime(thread_id, 10).unwrap();
+        assert!(RTKit::thread_is_realtime(thread_id).unwrap());
+
+        RTKit::reset_thread(thread_id).unwrap();
 
         let attr = get_sched_attr().unwrap();
-        assert!(attr.sched_policy > libc::SCHED_OTHER as u32);
-        assert_eq!(attr.sched_priority, 10);
+        assert_eq!(attr.sched_policy, libc::SCHED_OTHER as u32);
+        assert_eq!(attr.sched_priority, 0);
+        assert_eq!(attr.sched_nice, 0);
+    }
+
+    #[test]
+    fn test_demote_current_thread() {
+        // Use a separate thread, so that the test thread's scheduling is left alone
+        std::thread::spawn(|| {
+            let attr = sys::SchedAttr {
+                policy: libc::SCHED_BATCH as u32,
+                ..Default::default()
+            };
+            sys::sched_setattr(RTKit::current_thread_id().get(), &attr).unwrap();
+            assert_eq!(
+                get_sched_attr().unwrap().sched_policy,
+                libc::SCHED_BATCH as u32
+            );
+
+            RTKit::demote_current_thread().unwrap();
+
+            let attr = get_sched_attr().unwrap();
+            assert_eq!(attr.sched_policy, libc::SCHED_OTHER as u32);
+            assert_eq!(attr.sched_priority, 0);
+            assert_eq!(attr.sched_nice, 0);
+        })
+        .join()
+        .unwrap();
+    }
+
+    #[test]
+    fn test_reset_known() {
+        let rtkit = RTKit::new().unwrap();
+
+        let thread_id = RTKit::current_thread_id();
+        rtkit.make_thread_high_priority(thread_id, -10).unwrap();
+
+        assert!(rtkit.reset_known().is_ok());
+    }
+
+    #[test]
+    fn test_from_connection() {
+        let connection = Connection::system().unwrap();
+        let rtkit = RTKit::from_connection(connection).unwrap();
+
+        assert_eq!(rtkit.max_realtime_priority().unwrap(), 20);
     }
 }
@@ -0,0 +1,315 @@
+// SPDX-License-Identifier: MIT
+// SPDX-FileCopyrightText: Copyright (c) 2025 Asymptotic Inc.
+// SPDX-FileCopyrightText: Copyright (c) 2025 Sanchayan Maity
+
+//! A mock `rtkit` service, for testing code that uses this crate without a real daemon.
+//!
+//! [`MockRtkit`] implements the `org.freedesktop.RealtimeKit1` interface in-process, on a private
+//! peer-to-peer D-Bus connection. Passing the other end of that connection to
+//! [`RTKit::from_connection()`](crate::RTKit::from_connection) gives an `RTKit` that talks to the
+//! mock instead of the system bus.
+//!
+//! The mock does not change the scheduling of any threads. It records the requests it receives,
+//! and refuses those outside the range of its properties, as the real daemon would.
+//!
+//! ```
+//! use rtkit_rs::{MockRequest, MockRtkit, RTKit};
+//!
+//! # fn main() -> Result<(), rtkit_rs::RtkitError> {
+//! let mock = MockRtkit::new()?;
+//! let rtkit = RTKit::from_connection(mock.connection())?;
+//!
+//! assert_eq!(rtkit.max_realtime_priority()?, 20);
+//! rtkit.make_thread_realtime(1234, 10)?;
+//!
+//! assert_eq!(
+//!     mock.requests(),
+//!     vec![MockRequest::Realtime {
+//!         process_id: None,
+//!         thread_id: 1234,
+//!         priority: 10,
+//!     }]
+//! );
+//! # Ok(())
+//! # }
+//! ```
+
+use std::os::unix::net::UnixStream;
+use std::sync::{Arc, Mutex};
+
+use zbus::blocking::Connection;
+use zbus::blocking::connection::Builder;
+use zbus::fdo;
+
+use crate::{RTKIT_OBJECT_PATH, RTKIT_SERVICE_NAME, RtkitError};
+
+/// A request received by a [`MockRtkit`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum MockRequest {
+    /// A `MakeThreadHighPriority` or `MakeThreadHighPriorityWithPID` call.
+    HighPriority {
+        /// The process id, for `MakeThreadHighPriorityWithPID`.
+        process_id: Option<u64>,
+        /// The thread id.
+        thread_id: u64,
+        /// The requested nice level.
+        priority: i32,
+    },
+    /// A `MakeThreadRealtime` or `MakeThreadRealtimeWithPID` call.
+    Realtime {
+        /// The process id, for `MakeThreadRealtimeWithPID`.
+        process_id: Option<u64>,
+        /// The thread id.
+        thread_id: u64,
+        /// The requested real-time priority.
+        priority: u32,
+    },
+    /// A `ResetKnown` call.
+    ResetKnown,
+    /// A `ResetAll` call.
+    ResetAll,
+}
+
+struct Daemon {
+    max_realtime_priority: i32,
+    min_nice_level: i32,
+    rttime_usec_max: i64,
+    requests: Arc<Mutex<Vec<MockRequest>>>,
+}
+
+impl Daemon {
+    fn high_priority(
+        &self,
+        process_id: Option<u64>,
+        thread_id: u64,
+        priority: i32,
+    ) -> fdo::Result<()> {
+        if !(self.min_nice_level..=0).contains(&priority) {
+            return Err(fdo::Error::AccessDenied(format!(
+                "The minimum nice level is {}",
+                self.min_nice_level
+            )));
+        }
+
+        self.record(MockRequest::HighPriority {
+            process_id,
+            thread_id,
+            priority,
+        })
+    }
+
+    fn realtime(&self, process_id: Option<u64>, thread_id: u64, priority: u32) -> fdo::Result<()> {
+        if i64::from(priority) > i64::from(self.max_realtime_priority) {
+            return Err(fdo::Error::AccessDenied(format!(
+                "The maximum realtime priority is {}",
+                self.max_realtime_priority
+            )));
+        }
+
+        self.record(MockRequest::Realtime {
+            process_id,
+            thread_id,
+            priority,
+        })
+    }
+
+    fn record(&self, request: MockRequest) -> fdo::Result<()> {
+        self.requests.lock().unwrap().push(request);
+
+        Ok(())
+    }
+}
+
+#[zbus::interface(name = "org.freedesktop.RealtimeKit1")]
+impl Daemon {
+    #[zbus(property, name = "MaxRealtimePriority")]
+    fn max_realtime_priority(&self) -> i32 {
+        self.max_realtime_priority
+    }
+
+    #[zbus(property, name = "MinNiceLevel")]
+    fn min_nice_level(&self) -> i32 {
+        self.min_nice_level
+    }
+
+    #[zbus(property, name = "RTTimeUSecMax")]
+    fn rttime_usec_max(&self) -> i64 {
+        self.rttime_usec_max
+    }
+
+    #[zbus(name = "MakeThreadHighPriority")]
+    fn make_thread_high_priority(&self, thread_id: u64, priority: i32) -> fdo::Result<()> {
+        self.high_priority(None, thread_id, priority)
+    }
+
+    #[zbus(name = "MakeThreadHighPriorityWithPID")]
+    fn make_thread_high_priority_with_pid(
+        &self,
+        process_id: u64,
+        thread_id: u64,
+        priority: i32,
+    ) -> fdo::Result<()> {
+        self.high_priority(Some(process_id), thread_id, priority)
+    }
+
+    #[zbus(name = "MakeThreadRealtime")]
+    fn make_thread_realtime(&self, thread_id: u64, priority: u32) -> fdo::Result<()> {
+        self.realtime(None, thread_id, priority)
+    }
+
+    #[zbus(name = "MakeThreadRealtimeWithPID")]
+    fn make_thread_realtime_with_pid(
+        &self,
+        process_id: u64,
+        thread_id: u64,
+        priority: u32,
+    ) -> fdo::Result<()> {
+        self.realtime(Some(process_id), thread_id, priority)
+    }
+
+    #[zbus(name = "ResetKnown")]
+    fn reset_known(&self) -> fdo::Result<()> {
+        self.record(MockRequest::ResetKnown)
+    }
+
+    #[zbus(name = "ResetAll")]
+    fn reset_all(&self) -> fdo::Result<()> {
+        self.record(MockRequest::ResetAll)
+    }
+}
+
+// Just enough of the bus daemon's interface for `RTKit` to see that the service is available
+struct Bus;
+
+#[zbus::interface(name = "org.freedesktop.DBus")]
+impl Bus {
+    fn list_names(&self) -> Vec<String> {
+        vec![
+            "org.freedesktop.DBus".to_string(),
+            RTKIT_SERVICE_NAME.to_string(),
+        ]
+    }
+
+    fn get_name_owner(&self, name: String) -> fdo::Result<String> {
+        if name != RTKIT_SERVICE_NAME {
+            return Err(fdo::Error::NameHasNoOwner(name));
+        }
+
+        Ok(":1.0".to_string())
+    }
+}
+
+/// A mock `rtkit` service, running on a private D-Bus connection. See the
+/// [module documentation](self) for an example.
+///
+/// The service stops when the `MockRtkit` is dropped.
+pub struct MockRtkit {
+    client: Connection,
+    _server: Connection,
+    requests: Arc<Mutex<Vec<MockRequest>>>,
+}
+
+impl MockRtkit {
+    /// Start a mock service with the same property values as a default `rtkit` configuration,
+    /// i.e. a maximum real-time priority of 20, a minimum nice level of -15, and a maximum
+    /// `RLIMIT_RTTIME` of 200ms.
+    pub fn new() -> Result<MockRtkit, RtkitError> {
+        Self::with_properties(20, -15, 200000)
+    }
+
+    /// Start a mock service with the given property values.
+    pub fn with_properties(
+        max_realtime_priority: i32,
+        min_nice_level: i32,
+        rttime_usec_max: i64,
+    ) -> Result<MockRtkit, RtkitError> {
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let daemon = Daemon {
+            max_realtime_priority,
+            min_nice_level,
+            rttime_usec_max,
+            requests: requests.clone(),
+        };
+
+        let (client, server) = UnixStream::pair()?;
+
+        // Both ends must complete the handshake at the same time
+        let server = std::thread::spawn(move || {
+            Builder::unix_stream(server)
+                .server(zbus::Guid::generate())?
+                .p2p()
+                .serve_at("/org/freedesktop/DBus", Bus)?
+                .serve_at(RTKIT_OBJECT_PATH, daemon)?
+                .build()
+        });
+
+        let client = Builder::unix_stream(client).p2p().build()?;
+        let server = server.join().expect("mock rtkit server panicked")?;
+
+        Ok(MockRtkit {
+            client,
+            _server: server,
+            requests,
+        })
+    }
+
+    /// Returns a connection to the mock service, to be passed to
+    /// [`RTKit::from_connection()`](crate::RTKit::from_connection).
+    pub fn connection(&self) -> Connection {
+        self.client.clone()
+    }
+
+    /// Returns the requests that the mock service has accepted so far, in the order that they
+    /// were received.
+    pub fn requests(&self) -> Vec<MockRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+    use crate::RTKit;
+
+    #[test]
+    fn test_mock_rtkit() {
+        let mock = MockRtkit::with_properties(10, -5, 100000).unwrap();
+        let rtkit = RTKit::from_connection(mock.connection()).unwrap();
+
+        assert_eq!(rtkit.max_realtime_priority().unwrap(), 10);
+        assert_eq!(rtkit.min_nice_level().unwrap(), -5);
+        assert_eq!(rtkit.rttime_usec_max().unwrap(), 100000);
+        assert_eq!(rtkit.properties().unwrap().rttime_usec_max, 100000);
+
+        rtkit.make_thread_high_priority(1, -5).unwrap();
+        rtkit.make_thread_realtime_with_pid(2, 3, 10).unwrap();
+        rtkit.reset_known().unwrap();
+
+        assert!(matches!(
+            rtkit.make_thread_realtime(1, 11),
+            Err(RtkitError::PermissionDenied { message: Some(_) })
+        ));
+        assert!(matches!(
+            rtkit.make_thread_high_priority(1, -6),
+            Err(RtkitError::PermissionDenied { .. })
+        ));
+
+        assert_eq!(
+            mock.requests(),
+            vec![
+                MockRequest::HighPriority {
+                    process_id: None,
+                    thread_id: 1,
+                    priority: -5,
+                },
+                MockRequest::Realtime {
+                    process_id: Some(2),
+                    thread_id: 3,
+                    priority: 10,
+                },
+                MockRequest::ResetKnown,
+            ]
+        );
+    }
+}
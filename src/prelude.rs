@@ -0,0 +1,24 @@
+// SPDX-License-Identifier: MIT
+// SPDX-FileCopyrightText: Copyright (c) 2025 Asymptotic Inc.
+// SPDX-FileCopyrightText: Copyright (c) 2025 Sanchayan Maity
+
+//! The types most commonly needed when using this crate, for glob importing.
+//!
+//! ```no_run
+//! use rtkit_rs::prelude::*;
+//!
+//! # fn main() -> Result<(), RtkitError> {
+//! let rtkit = RTKit::new()?;
+//! let thread_id: ThreadId = RTKit::current_thread_id();
+//!
+//! rtkit.make_thread_realtime(thread_id, RealtimePriority::from(10))?;
+//! assert_eq!(RTKit::current_scheduling_policy(thread_id)?, SchedPolicy::Rr);
+//! # Ok(())
+//! # }
+//! ```
+
+#[cfg(feature = "async")]
+pub use crate::AsyncRTKit;
+pub use crate::{
+    NiceLevel, ProcessId, RTKit, RTKitBuilder, RealtimePriority, RtkitError, SchedPolicy, ThreadId,
+};
@@ -0,0 +1,133 @@
+// SPDX-License-Identifier: MIT
+// SPDX-FileCopyrightText: Copyright (c) 2025 Asymptotic Inc.
+// SPDX-FileCopyrightText: Copyright (c) 2025 Sanchayan Maity
+
+use crate::RtkitError;
+
+/// A nice level, as requested by
+/// [`RTKit::make_thread_high_priority()`](crate::RTKit::make_thread_high_priority) and related
+/// methods.
+///
+/// This is distinct from [`RealtimePriority`], so that the two cannot be mixed up. Note that
+/// _lower_ (more negative) nice levels correspond to _higher_ priority.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NiceLevel(i32);
+
+impl NiceLevel {
+    /// Creates a nice level of `level`, checking that it is between `min` (usually
+    /// [`RTKit::min_nice_level()`](crate::RTKit::min_nice_level)) and 0, or returning
+    /// [`RtkitError::NiceLevelOutOfRange`] otherwise.
+    pub fn new(level: i32, min: i32) -> Result<NiceLevel, RtkitError> {
+        if !(min..=0).contains(&level) {
+            return Err(RtkitError::NiceLevelOutOfRange {
+                requested: level,
+                min,
+            });
+        }
+
+        Ok(NiceLevel(level))
+    }
+
+    /// Returns the nice level as an integer.
+    pub fn get(self) -> i32 {
+        self.0
+    }
+}
+
+/// Creates a nice level without checking its range.
+impl From<i32> for NiceLevel {
+    fn from(level: i32) -> NiceLevel {
+        NiceLevel(level)
+    }
+}
+
+impl From<NiceLevel> for i32 {
+    fn from(level: NiceLevel) -> i32 {
+        level.0
+    }
+}
+
+/// A real-time priority, as requested by
+/// [`RTKit::make_thread_realtime()`](crate::RTKit::make_thread_realtime) and related methods.
+///
+/// This is distinct from [`NiceLevel`], so that the two cannot be mixed up. Higher values
+/// correspond to higher priority.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RealtimePriority(u32);
+
+impl RealtimePriority {
+    /// Creates a real-time priority of `priority`, checking that it is no higher than `max`
+    /// (usually [`RTKit::max_realtime_priority_u32()`](crate::RTKit::max_realtime_priority_u32)),
+    /// or returning [`RtkitError::RealtimePriorityOutOfRange`] otherwise.
+    pub fn new(priority: u32, max: u32) -> Result<RealtimePriority, RtkitError> {
+        if priority > max {
+            return Err(RtkitError::RealtimePriorityOutOfRange {
+                requested: priority,
+                max,
+            });
+        }
+
+        Ok(RealtimePriority(priority))
+    }
+
+    /// Returns the priority as an integer.
+    pub fn get(self) -> u32 {
+        self.0
+    }
+}
+
+/// Creates a real-time priority without checking its range.
+impl From<u32> for RealtimePriority {
+    fn from(priority: u32) -> RealtimePriority {
+        RealtimePriority(priority)
+    }
+}
+
+/// Fails if `priority` is negative, e.g. when converting the result of
+/// [`RTKit::max_realtime_priority()`](crate::RTKit::max_realtime_priority).
+impl TryFrom<i32> for RealtimePriority {
+    type Error = std::num::TryFromIntError;
+
+    fn try_from(priority: i32) -> Result<RealtimePriority, Self::Error> {
+        u32::try_from(priority).map(RealtimePriority)
+    }
+}
+
+impl From<RealtimePriority> for u32 {
+    fn from(priority: RealtimePriority) -> u32 {
+        priority.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nice_level() {
+        assert_eq!(NiceLevel::new(-10, -15).unwrap().get(), -10);
+        assert!(matches!(
+            NiceLevel::new(-20, -15),
+            Err(RtkitError::NiceLevelOutOfRange {
+                requested: -20,
+                min: -15
+            })
+        ));
+        assert!(NiceLevel::new(1, -15).is_err());
+        assert_eq!(i32::from(NiceLevel::from(-5)), -5);
+    }
+
+    #[test]
+    fn test_realtime_priority() {
+        assert_eq!(RealtimePriority::new(10, 20).unwrap().get(), 10);
+        assert!(matches!(
+            RealtimePriority::new(21, 20),
+            Err(RtkitError::RealtimePriorityOutOfRange {
+                requested: 21,
+                max: 20
+            })
+        ));
+        assert_eq!(RealtimePriority::try_from(5i32).unwrap().get(), 5);
+        assert!(RealtimePriority::try_from(-5i32).is_err());
+    }
+}
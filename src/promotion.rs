@@ -0,0 +1,110 @@
+// SPDX-License-Identifier: MIT
+// SPDX-FileCopyrightText: Copyright (c) 2025 Asymptotic Inc.
+// SPDX-FileCopyrightText: Copyright (c) 2025 Sanchayan Maity
+
+use crate::sys::{self, SchedAttr};
+use crate::{RtkitError, SchedPolicy, ThreadId};
+
+/// The outcome of [`RTKit::promote_current_thread()`](crate::RTKit::promote_current_thread).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PromotionOutcome {
+    /// The thread already had a real-time scheduling policy, so no request was made. Its priority
+    /// is not checked, and may differ from the one that was asked for.
+    AlreadyRealtime,
+    /// The thread was promoted, and now has the given real-time priority.
+    Promoted {
+        /// The thread's real-time priority after the promotion.
+        priority: u32,
+    },
+}
+
+/// The scheduling that a thread was granted, compared to what was requested, as returned by
+/// [`RTKit::promotion_report()`](crate::RTKit::promotion_report).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct PromotionReport {
+    /// The real-time priority that was requested.
+    pub requested: u32,
+    /// The scheduling policy that the thread has after the request.
+    pub granted_policy: SchedPolicy,
+    /// The real-time priority that the thread has after the request.
+    pub granted_priority: u32,
+    /// Whether the thread was granted a lower priority than it requested.
+    pub clamped: bool,
+}
+
+/// A guard returned by [`RTKit::promote_current_scoped()`](crate::RTKit::promote_current_scoped),
+/// which restores the thread's previous scheduling policy and priority when dropped.
+///
+/// Since errors cannot be returned from `drop()`, any failure to restore the previous scheduling
+/// is ignored (and logged, if the `tracing` feature is enabled) rather than causing a panic. Use
+/// [`RTKit::enter_realtime()`](crate::RTKit::enter_realtime) instead to handle such failures.
+#[must_use = "the thread's previous scheduling is restored as soon as the guard is dropped"]
+#[derive(Debug)]
+pub struct RealtimePromotion {
+    thread_id: ThreadId,
+    previous: SchedAttr,
+}
+
+impl RealtimePromotion {
+    pub(crate) fn new(thread_id: ThreadId, previous: SchedAttr) -> RealtimePromotion {
+        RealtimePromotion {
+            thread_id,
+            previous,
+        }
+    }
+
+    /// Returns the id of the thread that was promoted.
+    pub fn thread_id(&self) -> ThreadId {
+        self.thread_id
+    }
+}
+
+impl Drop for RealtimePromotion {
+    fn drop(&mut self) {
+        let result = sys::restore_sched_attr(self.thread_id.get(), &self.previous);
+
+        #[cfg(feature = "tracing")]
+        if let Err(e) = result {
+            tracing::warn!(
+                thread_id = self.thread_id.get(),
+                error = %e,
+                "failed to restore thread scheduling"
+            );
+        }
+        #[cfg(not(feature = "tracing"))]
+        let _ = result;
+    }
+}
+
+/// A token returned by [`RTKit::enter_realtime()`](crate::RTKit::enter_realtime), holding the
+/// thread's previous scheduling policy and priority until it is passed to
+/// [`RTKit::exit_realtime()`](crate::RTKit::exit_realtime).
+///
+/// Unlike [`RealtimePromotion`], dropping the token does nothing, so the thread's scheduling is
+/// only restored at an explicit point, which suits integration with C event loops and other FFI
+/// boundaries where drop order is awkward to control.
+#[must_use = "the thread's previous scheduling is only restored by passing this to exit_realtime()"]
+#[derive(Debug)]
+pub struct RealtimeToken {
+    thread_id: ThreadId,
+    previous: SchedAttr,
+}
+
+impl RealtimeToken {
+    pub(crate) fn new(thread_id: ThreadId, previous: SchedAttr) -> RealtimeToken {
+        RealtimeToken {
+            thread_id,
+            previous,
+        }
+    }
+
+    /// Returns the id of the thread that was promoted.
+    pub fn thread_id(&self) -> ThreadId {
+        self.thread_id
+    }
+
+    pub(crate) fn restore(self) -> Result<(), RtkitError> {
+        sys::restore_sched_attr(self.thread_id.get(), &self.previous)
+    }
+}
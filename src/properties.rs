@@ -0,0 +1,107 @@
+// SPDX-License-Identifier: MIT
+// SPDX-FileCopyrightText: Copyright (c) 2025 Asymptotic Inc.
+// SPDX-FileCopyrightText: Copyright (c) 2025 Sanchayan Maity
+
+use std::collections::HashMap;
+
+use zbus::zvariant::OwnedValue;
+
+use crate::{RtkitError, property_from_value};
+
+/// The properties exported by the `rtkit` daemon, as returned by
+/// [`RTKit::properties()`](crate::RTKit::properties).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct RtkitProperties {
+    /// The maximum permitted real-time priority value.
+    pub max_realtime_priority: i32,
+    /// The minimum permitted nice level value.
+    pub min_nice_level: i32,
+    /// The maximum time (in microseconds) that may be set for `RLIMIT_RTTIME`.
+    pub rttime_usec_max: i64,
+}
+
+impl RtkitProperties {
+    // Parses the result of an `org.freedesktop.DBus.Properties.GetAll` call
+    pub(crate) fn from_map(
+        properties: &HashMap<String, OwnedValue>,
+    ) -> Result<RtkitProperties, RtkitError> {
+        fn get<T>(
+            properties: &HashMap<String, OwnedValue>,
+            name: &'static str,
+        ) -> Result<T, RtkitError>
+        where
+            T: for<'a> TryFrom<&'a zbus::zvariant::Value<'a>>,
+        {
+            let value = properties
+                .get(name)
+                .ok_or(RtkitError::PropertyUnsupported { name })?;
+
+            property_from_value(name, value)
+        }
+
+        Ok(RtkitProperties {
+            max_realtime_priority: get(properties, "MaxRealtimePriority")?,
+            min_nice_level: get(properties, "MinNiceLevel")?,
+            rttime_usec_max: get(properties, "RTTimeUSecMax")?,
+        })
+    }
+}
+
+/// Formats the properties as a single line, for logging, e.g.
+/// `rtkit: max_rt_prio=20, min_nice=-15, rttime_max=200000us`.
+///
+/// This format is stable, so that it may be relied on by log processing tools.
+impl std::fmt::Display for RtkitProperties {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "rtkit: max_rt_prio={}, min_nice={}, rttime_max={}us",
+            self.max_realtime_priority, self.min_nice_level, self.rttime_usec_max
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_map() {
+        let mut properties = HashMap::new();
+        properties.insert("MaxRealtimePriority".to_string(), OwnedValue::from(20i32));
+        properties.insert("MinNiceLevel".to_string(), OwnedValue::from(-15i32));
+
+        assert!(matches!(
+            RtkitProperties::from_map(&properties),
+            Err(RtkitError::PropertyUnsupported {
+                name: "RTTimeUSecMax"
+            })
+        ));
+
+        properties.insert("RTTimeUSecMax".to_string(), OwnedValue::from(200000i64));
+
+        assert_eq!(
+            RtkitProperties::from_map(&properties).unwrap(),
+            RtkitProperties {
+                max_realtime_priority: 20,
+                min_nice_level: -15,
+                rttime_usec_max: 200000,
+            }
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        let properties = RtkitProperties {
+            max_realtime_priority: 20,
+            min_nice_level: -15,
+            rttime_usec_max: 200000,
+        };
+
+        assert_eq!(
+            properties.to_string(),
+            "rtkit: max_rt_prio=20, min_nice=-15, rttime_max=200000us"
+        );
+    }
+}
@@ -0,0 +1,213 @@
+// SPDX-License-Identifier: MIT
+// SPDX-FileCopyrightText: Copyright (c) 2025 Asymptotic Inc.
+// SPDX-FileCopyrightText: Copyright (c) 2025 Sanchayan Maity
+
+// The requests that can be made to the daemon, and the parsing of their replies. These are shared
+// by the blocking and async front-ends, which only differ in how the requests are sent, so that
+// both always send the same messages.
+
+use std::collections::HashMap;
+
+use zbus::Message;
+use zbus::zvariant::{OwnedValue, Value};
+
+use crate::{RTKIT_INTERFACE, RtkitError, RtkitProperties, property_from_value};
+
+const PROPERTIES_INTERFACE: &str = "org.freedesktop.DBus.Properties";
+
+// A method call to the daemon
+#[derive(Debug)]
+pub(crate) struct Request<'a, B> {
+    pub(crate) interface: &'static str,
+    pub(crate) method: &'a str,
+    pub(crate) body: B,
+}
+
+fn request<B>(interface: &'static str, method: &'static str, body: B) -> Request<'static, B> {
+    Request {
+        interface,
+        method,
+        body,
+    }
+}
+
+pub(crate) fn get_property(name: &'static str) -> Request<'static, (&'static str, &'static str)> {
+    request(PROPERTIES_INTERFACE, "Get", (RTKIT_INTERFACE, name))
+}
+
+pub(crate) fn get_all_properties() -> Request<'static, (&'static str,)> {
+    request(PROPERTIES_INTERFACE, "GetAll", (RTKIT_INTERFACE,))
+}
+
+pub(crate) fn make_thread_high_priority(
+    thread_id: u64,
+    priority: i32,
+) -> Request<'static, (u64, i32)> {
+    request(
+        RTKIT_INTERFACE,
+        "MakeThreadHighPriority",
+        (thread_id, priority),
+    )
+}
+
+pub(crate) fn make_thread_high_priority_with_pid(
+    process_id: u64,
+    thread_id: u64,
+    priority: i32,
+) -> Request<'static, (u64, u64, i32)> {
+    request(
+        RTKIT_INTERFACE,
+        "MakeThreadHighPriorityWithPID",
+        (process_id, thread_id, priority),
+    )
+}
+
+pub(crate) fn make_thread_realtime(thread_id: u64, priority: u32) -> Request<'static, (u64, u32)> {
+    request(RTKIT_INTERFACE, "MakeThreadRealtime", (thread_id, priority))
+}
+
+pub(crate) fn make_thread_realtime_with_pid(
+    process_id: u64,
+    thread_id: u64,
+    priority: u32,
+) -> Request<'static, (u64, u64, u32)> {
+    request(
+        RTKIT_INTERFACE,
+        "MakeThreadRealtimeWithPID",
+        (process_id, thread_id, priority),
+    )
+}
+
+pub(crate) fn reset_known() -> Request<'static, ()> {
+    request(RTKIT_INTERFACE, "ResetKnown", ())
+}
+
+pub(crate) fn reset_all() -> Request<'static, ()> {
+    request(RTKIT_INTERFACE, "ResetAll", ())
+}
+
+pub(crate) fn exit() -> Request<'static, ()> {
+    request(RTKIT_INTERFACE, "Exit", ())
+}
+
+// A call to any method of the daemon's interface, for `RTKit::call_raw()`
+pub(crate) fn raw<B>(method: &str, body: B) -> Request<'_, B> {
+    Request {
+        interface: RTKIT_INTERFACE,
+        method,
+        body,
+    }
+}
+
+// Older versions of the daemon do not export every property. Depending on the version of the
+// D-Bus implementation serving them, a `Get` for a missing property fails with `UnknownProperty`
+// or `InvalidArgs`, which are turned into `RtkitError::PropertyUnsupported`.
+pub(crate) fn property_error(name: &'static str, err: RtkitError) -> RtkitError {
+    match err {
+        RtkitError::Daemon {
+            name: ref error_name,
+            ..
+        } if error_name == "org.freedesktop.DBus.Error.UnknownProperty"
+            || error_name == "org.freedesktop.DBus.Error.InvalidArgs" =>
+        {
+            RtkitError::PropertyUnsupported { name }
+        }
+        err => err,
+    }
+}
+
+// Parses the reply to a `get_property()` request
+pub(crate) fn parse_property<T>(name: &'static str, reply: &Message) -> Result<T, RtkitError>
+where
+    T: for<'a> TryFrom<&'a Value<'a>>,
+{
+    let body = reply.body();
+    let value: Value = body.deserialize()?;
+
+    property_from_value(name, &value)
+}
+
+// Parses the reply to a `get_all_properties()` request
+pub(crate) fn parse_properties(reply: &Message) -> Result<RtkitProperties, RtkitError> {
+    let map: HashMap<String, OwnedValue> = reply.body().deserialize()?;
+
+    RtkitProperties::from_map(&map)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zbus::export::serde::ser::Serialize;
+    use zbus::zvariant::DynamicType;
+
+    fn signature<B: Serialize + DynamicType>(request: &Request<'_, B>) -> String {
+        Message::method_call("/", request.method)
+            .unwrap()
+            .interface(request.interface)
+            .unwrap()
+            .build(&request.body)
+            .unwrap()
+            .body()
+            .signature()
+            .to_string()
+    }
+
+    #[test]
+    fn test_requests() {
+        let request = make_thread_realtime(1, 10);
+        assert_eq!(request.interface, RTKIT_INTERFACE);
+        assert_eq!(request.method, "MakeThreadRealtime");
+        assert_eq!(signature(&request), "(tu)");
+
+        let request = make_thread_high_priority_with_pid(1, 2, -10);
+        assert_eq!(request.method, "MakeThreadHighPriorityWithPID");
+        assert_eq!(signature(&request), "(tti)");
+
+        let request = get_property("MaxRealtimePriority");
+        assert_eq!(request.interface, PROPERTIES_INTERFACE);
+        assert_eq!(request.body, (RTKIT_INTERFACE, "MaxRealtimePriority"));
+        assert_eq!(signature(&request), "(ss)");
+
+        assert_eq!(signature(&reset_known()), "");
+    }
+
+    // Both front-ends should make exactly the same requests for the same inputs
+    #[cfg(all(feature = "async", feature = "test-util", target_os = "linux"))]
+    #[test]
+    fn test_front_ends_match() {
+        use crate::{AsyncRTKit, MockRtkit, RTKit};
+
+        let blocking = MockRtkit::new().unwrap();
+        let rtkit = RTKit::from_connection(blocking.connection()).unwrap();
+
+        let properties = rtkit.properties().unwrap();
+        rtkit.make_thread_high_priority(1, -5).unwrap();
+        rtkit.make_thread_high_priority_with_pid(2, 3, -10).unwrap();
+        rtkit.make_thread_realtime(4, 10).unwrap();
+        rtkit.make_thread_realtime_with_pid(5, 6, 20).unwrap();
+        rtkit.reset_known().unwrap();
+        rtkit.reset_all().unwrap();
+
+        let non_blocking = MockRtkit::new().unwrap();
+        let async_properties = async_io::block_on(async {
+            let connection = non_blocking.connection().into_inner();
+            let rtkit = AsyncRTKit::from_connection(connection).await.unwrap();
+
+            rtkit.make_thread_high_priority(1, -5).await.unwrap();
+            rtkit
+                .make_thread_high_priority_with_pid(2, 3, -10)
+                .await
+                .unwrap();
+            rtkit.make_thread_realtime(4, 10).await.unwrap();
+            rtkit.make_thread_realtime_with_pid(5, 6, 20).await.unwrap();
+            rtkit.reset_known().await.unwrap();
+            rtkit.reset_all().await.unwrap();
+
+            rtkit.properties().await.unwrap()
+        });
+
+        assert_eq!(properties, async_properties);
+        assert_eq!(blocking.requests(), non_blocking.requests());
+        assert_eq!(blocking.requests().len(), 6);
+    }
+}
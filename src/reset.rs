@@ -0,0 +1,33 @@
+// SPDX-License-Identifier: MIT
+// SPDX-FileCopyrightText: Copyright (c) 2025 Asymptotic Inc.
+// SPDX-FileCopyrightText: Copyright (c) 2025 Sanchayan Maity
+
+use crate::RTKit;
+
+// Calls `ResetKnown` when dropped. This is shared by an `RTKit` built with
+// `RTKitBuilder::reset_known_on_drop()` and all its clones, so that the reset happens when the
+// last of them is dropped.
+#[derive(Debug)]
+pub(crate) struct ResetKnownOnDrop {
+    // A copy of the `RTKit` that owns this, without its own `ResetKnownOnDrop`
+    rtkit: RTKit,
+}
+
+impl ResetKnownOnDrop {
+    pub(crate) fn new(rtkit: RTKit) -> ResetKnownOnDrop {
+        ResetKnownOnDrop { rtkit }
+    }
+}
+
+impl Drop for ResetKnownOnDrop {
+    fn drop(&mut self) {
+        let result = self.rtkit.reset_known();
+
+        #[cfg(feature = "tracing")]
+        if let Err(e) = result {
+            tracing::warn!(error = %e, "failed to reset known threads");
+        }
+        #[cfg(not(feature = "tracing"))]
+        let _ = result;
+    }
+}
@@ -0,0 +1,82 @@
+// SPDX-License-Identifier: MIT
+// SPDX-FileCopyrightText: Copyright (c) 2025 Asymptotic Inc.
+// SPDX-FileCopyrightText: Copyright (c) 2025 Sanchayan Maity
+
+use crate::RtkitError;
+
+/// A Linux thread scheduling policy, as reported by `sched_getattr()`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SchedPolicy {
+    /// The default time-sharing policy (`SCHED_OTHER`).
+    Other,
+    /// First-in, first-out real-time scheduling (`SCHED_FIFO`).
+    Fifo,
+    /// Round-robin real-time scheduling (`SCHED_RR`).
+    Rr,
+    /// Time-sharing for batch, non-interactive work (`SCHED_BATCH`).
+    Batch,
+    /// Very low priority background work (`SCHED_IDLE`).
+    Idle,
+    /// Earliest deadline first scheduling (`SCHED_DEADLINE`).
+    Deadline,
+}
+
+impl SchedPolicy {
+    /// Returns whether this is a real-time policy (i.e. `SCHED_FIFO` or `SCHED_RR`).
+    pub fn is_realtime(&self) -> bool {
+        matches!(self, SchedPolicy::Fifo | SchedPolicy::Rr)
+    }
+}
+
+impl TryFrom<u32> for SchedPolicy {
+    type Error = RtkitError;
+
+    fn try_from(policy: u32) -> Result<Self, Self::Error> {
+        match policy {
+            0 => Ok(SchedPolicy::Other),
+            1 => Ok(SchedPolicy::Fifo),
+            2 => Ok(SchedPolicy::Rr),
+            3 => Ok(SchedPolicy::Batch),
+            5 => Ok(SchedPolicy::Idle),
+            6 => Ok(SchedPolicy::Deadline),
+            _ => Err(RtkitError::UnknownSchedPolicy(policy)),
+        }
+    }
+}
+
+impl From<SchedPolicy> for u32 {
+    fn from(policy: SchedPolicy) -> u32 {
+        match policy {
+            SchedPolicy::Other => 0,
+            SchedPolicy::Fifo => 1,
+            SchedPolicy::Rr => 2,
+            SchedPolicy::Batch => 3,
+            SchedPolicy::Idle => 5,
+            SchedPolicy::Deadline => 6,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_policy_conversion() {
+        for policy in [
+            SchedPolicy::Other,
+            SchedPolicy::Fifo,
+            SchedPolicy::Rr,
+            SchedPolicy::Batch,
+            SchedPolicy::Idle,
+            SchedPolicy::Deadline,
+        ] {
+            assert_eq!(SchedPolicy::try_from(u32::from(policy)).unwrap(), policy);
+        }
+
+        assert!(matches!(
+            SchedPolicy::try_from(4),
+            Err(RtkitError::UnknownSchedPolicy(4))
+        ));
+    }
+}
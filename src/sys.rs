@@ -0,0 +1,471 @@
+// SPDX-License-Identifier: MIT
+// SPDX-FileCopyrightText: Copyright (c) 2025 Asymptotic Inc.
+// SPDX-FileCopyrightText: Copyright (c) 2025 Sanchayan Maity
+
+// Platform-specific helpers. `rtkit` is only available on Linux, so other platforms get fallbacks
+// that either return a best-effort value, or `RtkitError::Unsupported`.
+
+use crate::RtkitError;
+
+// With the `rustix` feature, the process and thread id helpers use its safe wrappers instead of
+// calling into libc. The scheduling attributes are still read and set through libc, as rustix does
+// not wrap `sched_getattr()` or `sched_setattr()`.
+#[cfg(all(target_os = "linux", feature = "rustix"))]
+pub(crate) fn gettid() -> u64 {
+    rustix::thread::gettid().as_raw_nonzero().get() as u64
+}
+
+// glibc (since 2.30) and musl provide a `gettid()` wrapper, otherwise we make the raw syscall
+#[cfg(all(
+    target_os = "linux",
+    not(feature = "rustix"),
+    any(target_env = "gnu", target_env = "musl")
+))]
+pub(crate) fn gettid() -> u64 {
+    unsafe { libc::gettid() as u64 }
+}
+
+#[cfg(all(
+    target_os = "linux",
+    not(feature = "rustix"),
+    not(any(target_env = "gnu", target_env = "musl"))
+))]
+pub(crate) fn gettid() -> u64 {
+    let ret = unsafe { libc::syscall(libc::SYS_gettid) };
+
+    // `gettid()` cannot fail, but don't let a bogus id reach the daemon if the syscall somehow
+    // does (e.g. under a seccomp filter that makes it return `-1`)
+    if ret < 0 {
+        return std::process::id() as u64;
+    }
+
+    ret as u64
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn gettid() -> u64 {
+    std::process::id() as u64
+}
+
+#[cfg(all(target_os = "linux", feature = "rustix"))]
+pub(crate) fn set_rttime_limit(rttime_usec: u64) -> Result<(), RtkitError> {
+    let rlim = rustix::process::Rlimit {
+        current: Some(rttime_usec),
+        maximum: Some(rttime_usec),
+    };
+
+    rustix::process::setrlimit(rustix::process::Resource::Rttime, rlim)
+        .map_err(std::io::Error::from)?;
+
+    Ok(())
+}
+
+#[cfg(all(target_os = "linux", not(feature = "rustix")))]
+pub(crate) fn set_rttime_limit(rttime_usec: u64) -> Result<(), RtkitError> {
+    let rlim = libc::rlimit {
+        rlim_cur: rttime_usec,
+        rlim_max: rttime_usec,
+    };
+
+    let ret = unsafe { libc::setrlimit(libc::RLIMIT_RTTIME, &rlim) };
+    if ret < 0 {
+        return Err(std::io::Error::last_os_error().into());
+    }
+
+    Ok(())
+}
+
+// Converts a process id to a `pid_t`, failing with `EINVAL` rather than truncating it, which could
+// turn it into another valid process id, or 0 (i.e. the current process)
+#[cfg(target_os = "linux")]
+fn pid_from_u64(process_id: u64) -> Result<libc::pid_t, RtkitError> {
+    libc::pid_t::try_from(process_id)
+        .map_err(|_| std::io::Error::from_raw_os_error(libc::EINVAL).into())
+}
+
+// Converts a thread id to a `pid_t`, failing with `RtkitError::NoSuchThread` rather than
+// truncating it, or passing on 0, either of which the kernel could take to mean the calling thread
+#[cfg(target_os = "linux")]
+fn tid_from_u64(thread_id: u64) -> Result<libc::pid_t, RtkitError> {
+    libc::pid_t::try_from(thread_id)
+        .ok()
+        .filter(|&tid| tid > 0)
+        .ok_or(RtkitError::NoSuchThread { thread_id })
+}
+
+// Checks that `thread_id` is valid for the helpers here, before making requests to the daemon
+// that they depend on
+#[cfg(target_os = "linux")]
+pub(crate) fn check_thread_id(thread_id: u64) -> Result<(), RtkitError> {
+    tid_from_u64(thread_id).map(drop)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn check_thread_id(_thread_id: u64) -> Result<(), RtkitError> {
+    Ok(())
+}
+
+// Sets `RLIMIT_RTTIME` for another process, which requires the same privileges as sending it a
+// signal (or `CAP_SYS_RESOURCE` to raise its hard limit)
+#[cfg(all(target_os = "linux", feature = "rustix"))]
+pub(crate) fn set_process_rttime_limit(
+    process_id: u64,
+    rttime_usec: u64,
+) -> Result<(), RtkitError> {
+    let rlim = rustix::process::Rlimit {
+        current: Some(rttime_usec),
+        maximum: Some(rttime_usec),
+    };
+
+    // A process id of 0 (i.e. `None`) refers to the current process, as with `prlimit()`
+    let pid = rustix::process::Pid::from_raw(pid_from_u64(process_id)?);
+
+    match rustix::process::prlimit(pid, rustix::process::Resource::Rttime, rlim) {
+        Ok(_) => Ok(()),
+        Err(rustix::io::Errno::PERM) => Err(RtkitError::RlimitPermissionDenied { process_id }),
+        Err(e) => Err(std::io::Error::from(e).into()),
+    }
+}
+
+#[cfg(all(target_os = "linux", not(feature = "rustix")))]
+pub(crate) fn set_process_rttime_limit(
+    process_id: u64,
+    rttime_usec: u64,
+) -> Result<(), RtkitError> {
+    let rlim = libc::rlimit {
+        rlim_cur: rttime_usec,
+        rlim_max: rttime_usec,
+    };
+
+    let pid = pid_from_u64(process_id)?;
+
+    let ret = unsafe { libc::prlimit(pid, libc::RLIMIT_RTTIME, &rlim, std::ptr::null_mut()) };
+    if ret < 0 {
+        let err = std::io::Error::last_os_error();
+        if err.kind() == std::io::ErrorKind::PermissionDenied {
+            return Err(RtkitError::RlimitPermissionDenied { process_id });
+        }
+
+        return Err(err.into());
+    }
+
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn set_process_rttime_limit(
+    _process_id: u64,
+    _rttime_usec: u64,
+) -> Result<(), RtkitError> {
+    Err(RtkitError::Unsupported)
+}
+
+// Returns the hard `RLIMIT_RTTIME` of the current process, or `None` if it is unlimited
+#[cfg(all(target_os = "linux", feature = "rustix"))]
+pub(crate) fn rttime_limit() -> Result<Option<u64>, RtkitError> {
+    Ok(rustix::process::getrlimit(rustix::process::Resource::Rttime).maximum)
+}
+
+#[cfg(all(target_os = "linux", not(feature = "rustix")))]
+pub(crate) fn rttime_limit() -> Result<Option<u64>, RtkitError> {
+    let mut rlim = libc::rlimit {
+        rlim_cur: 0,
+        rlim_max: 0,
+    };
+
+    let ret = unsafe { libc::getrlimit(libc::RLIMIT_RTTIME, &mut rlim) };
+    if ret < 0 {
+        return Err(std::io::Error::last_os_error().into());
+    }
+
+    Ok((rlim.rlim_max != libc::RLIM_INFINITY).then_some(rlim.rlim_max))
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn rttime_limit() -> Result<Option<u64>, RtkitError> {
+    Err(RtkitError::Unsupported)
+}
+
+// Returns the soft `RLIMIT_NICE` of the current process, which is the one the kernel checks, or
+// `None` if it is unlimited
+#[cfg(all(target_os = "linux", feature = "rustix"))]
+pub(crate) fn nice_limit() -> Result<Option<u64>, RtkitError> {
+    Ok(rustix::process::getrlimit(rustix::process::Resource::Nice).current)
+}
+
+#[cfg(all(target_os = "linux", not(feature = "rustix")))]
+pub(crate) fn nice_limit() -> Result<Option<u64>, RtkitError> {
+    let mut rlim = libc::rlimit {
+        rlim_cur: 0,
+        rlim_max: 0,
+    };
+
+    let ret = unsafe { libc::getrlimit(libc::RLIMIT_NICE, &mut rlim) };
+    if ret < 0 {
+        return Err(std::io::Error::last_os_error().into());
+    }
+
+    Ok((rlim.rlim_cur != libc::RLIM_INFINITY).then_some(rlim.rlim_cur))
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn nice_limit() -> Result<Option<u64>, RtkitError> {
+    Err(RtkitError::Unsupported)
+}
+
+// Sets the nice level of a single thread, which is what `setpriority(PRIO_PROCESS)` does on Linux
+// when given a thread id
+#[cfg(all(target_os = "linux", feature = "rustix"))]
+pub(crate) fn set_thread_nice(thread_id: u64, nice: i32) -> Result<(), RtkitError> {
+    let pid = rustix::process::Pid::from_raw(tid_from_u64(thread_id)?)
+        .ok_or(RtkitError::NoSuchThread { thread_id })?;
+
+    rustix::process::setpriority_process(Some(pid), nice)
+        .map_err(|e| std::io::Error::from(e).into())
+}
+
+#[cfg(all(target_os = "linux", not(feature = "rustix")))]
+pub(crate) fn set_thread_nice(thread_id: u64, nice: i32) -> Result<(), RtkitError> {
+    let tid = tid_from_u64(thread_id)?;
+
+    let ret = unsafe { libc::setpriority(libc::PRIO_PROCESS, tid as libc::id_t, nice) };
+    if ret < 0 {
+        return Err(std::io::Error::last_os_error().into());
+    }
+
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn set_thread_nice(_thread_id: u64, _nice: i32) -> Result<(), RtkitError> {
+    Err(RtkitError::Unsupported)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn set_rttime_limit(_rttime_usec: u64) -> Result<(), RtkitError> {
+    Err(RtkitError::Unsupported)
+}
+
+// A portable copy of the kernel's `struct sched_attr`.
+#[derive(Clone, Copy, Debug, Default)]
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+pub(crate) struct SchedAttr {
+    pub(crate) policy: u32,
+    pub(crate) flags: u64,
+    pub(crate) nice: i32,
+    pub(crate) priority: u32,
+    pub(crate) runtime: u64,
+    pub(crate) deadline: u64,
+    pub(crate) period: u64,
+}
+
+// `sched_getattr()` and `sched_setattr()` were added in Linux 3.14, and fail with `ENOSYS` before
+// that
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+pub(crate) fn sched_attr_error(err: std::io::Error) -> RtkitError {
+    if err.raw_os_error() == Some(libc::ENOSYS) {
+        return RtkitError::KernelTooOld;
+    }
+
+    err.into()
+}
+
+#[cfg(target_os = "linux")]
+pub(crate) fn sched_getattr(thread_id: u64) -> Result<SchedAttr, RtkitError> {
+    let tid = tid_from_u64(thread_id)?;
+    let mut attr: libc::sched_attr = unsafe { std::mem::zeroed() };
+
+    let ret = unsafe {
+        libc::syscall(
+            libc::SYS_sched_getattr,
+            tid,
+            &mut attr as *mut libc::sched_attr,
+            std::mem::size_of::<libc::sched_attr>() as libc::c_uint,
+            0 as libc::c_uint,
+        )
+    };
+    if ret < 0 {
+        return Err(sched_attr_error(std::io::Error::last_os_error()));
+    }
+
+    Ok(SchedAttr {
+        policy: attr.sched_policy,
+        flags: attr.sched_flags,
+        nice: attr.sched_nice,
+        priority: attr.sched_priority,
+        runtime: attr.sched_runtime,
+        deadline: attr.sched_deadline,
+        period: attr.sched_period,
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn sched_getattr(_thread_id: u64) -> Result<SchedAttr, RtkitError> {
+    Err(RtkitError::Unsupported)
+}
+
+#[cfg(target_os = "linux")]
+pub(crate) fn sched_setattr(thread_id: u64, attr: &SchedAttr) -> Result<(), RtkitError> {
+    let tid = tid_from_u64(thread_id)?;
+    let attr = libc::sched_attr {
+        size: std::mem::size_of::<libc::sched_attr>() as u32,
+        sched_policy: attr.policy,
+        sched_flags: attr.flags,
+        sched_nice: attr.nice,
+        sched_priority: attr.priority,
+        sched_runtime: attr.runtime,
+        sched_deadline: attr.deadline,
+        sched_period: attr.period,
+    };
+
+    let ret = unsafe {
+        libc::syscall(
+            libc::SYS_sched_setattr,
+            tid,
+            &attr as *const libc::sched_attr,
+            0 as libc::c_uint,
+        )
+    };
+    if ret < 0 {
+        return Err(sched_attr_error(std::io::Error::last_os_error()));
+    }
+
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn sched_setattr(_thread_id: u64, _attr: &SchedAttr) -> Result<(), RtkitError> {
+    Err(RtkitError::Unsupported)
+}
+
+// Restricts a thread to running on the given CPUs
+#[cfg(target_os = "linux")]
+pub(crate) fn set_affinity(thread_id: u64, cpus: &[usize]) -> Result<(), RtkitError> {
+    let tid = tid_from_u64(thread_id)?;
+    let mut set: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+
+    for &cpu in cpus {
+        if cpu >= libc::CPU_SETSIZE as usize {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("CPU {cpu} is out of range"),
+            )
+            .into());
+        }
+
+        unsafe { libc::CPU_SET(cpu, &mut set) };
+    }
+
+    let ret = unsafe { libc::sched_setaffinity(tid, std::mem::size_of::<libc::cpu_set_t>(), &set) };
+    if ret < 0 {
+        return Err(std::io::Error::last_os_error().into());
+    }
+
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn set_affinity(_thread_id: u64, _cpus: &[usize]) -> Result<(), RtkitError> {
+    Err(RtkitError::Unsupported)
+}
+
+// Restores the scheduling parameters previously read with `sched_getattr()`. Unprivileged threads
+// may not clear `SCHED_FLAG_RESET_ON_FORK` once it is set (as rtkit does), so that is preserved.
+pub(crate) fn restore_sched_attr(thread_id: u64, previous: &SchedAttr) -> Result<(), RtkitError> {
+    let current = sched_getattr(thread_id)?;
+
+    let mut attr = *previous;
+    attr.flags |= current.flags & SCHED_FLAG_RESET_ON_FORK;
+
+    sched_setattr(thread_id, &attr)
+}
+
+pub(crate) const SCHED_FLAG_RESET_ON_FORK: u64 = 0x01;
+
+// Lists the thread ids in a `/proc/<pid>/task` directory
+#[cfg(target_os = "linux")]
+pub(crate) fn task_ids(task_dir: &str) -> Result<Vec<u64>, RtkitError> {
+    let mut ids = Vec::new();
+
+    for entry in std::fs::read_dir(task_dir)? {
+        // Threads can exit while we are iterating, in which case we skip them
+        let Ok(entry) = entry else {
+            continue;
+        };
+
+        if let Some(id) = entry.file_name().to_str().and_then(|n| n.parse().ok()) {
+            ids.push(id);
+        }
+    }
+
+    ids.sort_unstable();
+
+    Ok(ids)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn task_ids(_task_dir: &str) -> Result<Vec<u64>, RtkitError> {
+    Err(RtkitError::Unsupported)
+}
+
+// Checks whether `thread_id` is listed in a `/proc/<pid>/task` directory
+#[cfg(target_os = "linux")]
+pub(crate) fn task_exists(task_dir: &str, thread_id: u64) -> Result<bool, RtkitError> {
+    Ok(std::fs::exists(format!("{task_dir}/{thread_id}"))?)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn task_exists(_task_dir: &str, _thread_id: u64) -> Result<bool, RtkitError> {
+    Err(RtkitError::Unsupported)
+}
+
+// Lists the thread ids in a `/proc/<pid>/task` directory whose name (as reported in `comm`) is
+// `name`
+#[cfg(target_os = "linux")]
+pub(crate) fn task_ids_named(task_dir: &str, name: &str) -> Result<Vec<u64>, RtkitError> {
+    let mut ids = task_ids(task_dir)?;
+
+    // As before, threads that have exited are skipped
+    ids.retain(|id| {
+        std::fs::read_to_string(format!("{task_dir}/{id}/comm"))
+            .is_ok_and(|comm| comm.trim_end_matches('\n') == name)
+    });
+
+    Ok(ids)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn task_ids_named(_task_dir: &str, _name: &str) -> Result<Vec<u64>, RtkitError> {
+    Err(RtkitError::Unsupported)
+}
+
+pub(crate) const CAP_SYS_NICE: u32 = 23;
+
+// Parses the effective capability set from the contents of a `/proc/<pid>/status` file
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+pub(crate) fn parse_effective_capabilities(status: &str) -> Option<u64> {
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix("CapEff:"))
+        .and_then(|caps| u64::from_str_radix(caps.trim(), 16).ok())
+}
+
+// Checks whether the current process has `capability` in its effective set
+#[cfg(target_os = "linux")]
+pub(crate) fn has_capability(capability: u32) -> Result<bool, RtkitError> {
+    let status = std::fs::read_to_string("/proc/self/status")?;
+
+    let caps = parse_effective_capabilities(&status).ok_or_else(|| {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "no CapEff entry in /proc/self/status",
+        )
+    })?;
+
+    Ok(caps & (1 << capability) != 0)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn has_capability(_capability: u32) -> Result<bool, RtkitError> {
+    Err(RtkitError::Unsupported)
+}
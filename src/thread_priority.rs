@@ -0,0 +1,101 @@
+// SPDX-License-Identifier: MIT
+// SPDX-FileCopyrightText: Copyright (c) 2025 Asymptotic Inc.
+// SPDX-FileCopyrightText: Copyright (c) 2025 Sanchayan Maity
+
+// Conversions to and from the `thread-priority` crate's types, with the `thread-priority` feature.
+// Its `ThreadPriority::Os` values cannot be read or created outside that crate, and `Min` and
+// `Max` depend on the policy (and, for `rtkit`, on the daemon's configuration), so only
+// cross-platform values are converted.
+
+use ::thread_priority::{RealtimeThreadSchedulePolicy, ThreadPriority, ThreadPriorityValue};
+
+use crate::{RealtimePriority, RtkitError, SchedPolicy};
+
+/// Converts a [`ThreadPriority::Crossplatform`] value, which uses the same 0-99 range as Linux
+/// real-time priorities. Other variants fail with [`RtkitError::ThreadPriorityNotConvertible`].
+impl TryFrom<ThreadPriority> for RealtimePriority {
+    type Error = RtkitError;
+
+    fn try_from(priority: ThreadPriority) -> Result<RealtimePriority, RtkitError> {
+        match priority {
+            ThreadPriority::Crossplatform(value) => Ok(u32::from(u8::from(value)).into()),
+            priority => Err(RtkitError::ThreadPriorityNotConvertible(format!(
+                "{priority:?}"
+            ))),
+        }
+    }
+}
+
+/// Converts to a [`ThreadPriority::Crossplatform`] value, failing with
+/// [`RtkitError::ThreadPriorityNotConvertible`] if the priority is above 99.
+impl TryFrom<RealtimePriority> for ThreadPriority {
+    type Error = RtkitError;
+
+    fn try_from(priority: RealtimePriority) -> Result<ThreadPriority, RtkitError> {
+        u8::try_from(priority.get())
+            .ok()
+            .and_then(|value| ThreadPriorityValue::try_from(value).ok())
+            .map(ThreadPriority::Crossplatform)
+            .ok_or_else(|| RtkitError::ThreadPriorityNotConvertible(format!("{priority:?}")))
+    }
+}
+
+impl From<RealtimeThreadSchedulePolicy> for SchedPolicy {
+    fn from(policy: RealtimeThreadSchedulePolicy) -> SchedPolicy {
+        match policy {
+            RealtimeThreadSchedulePolicy::Fifo => SchedPolicy::Fifo,
+            RealtimeThreadSchedulePolicy::RoundRobin => SchedPolicy::Rr,
+            RealtimeThreadSchedulePolicy::Deadline => SchedPolicy::Deadline,
+        }
+    }
+}
+
+/// Fails with [`RtkitError::ThreadPriorityNotConvertible`] for the non-real-time policies.
+impl TryFrom<SchedPolicy> for RealtimeThreadSchedulePolicy {
+    type Error = RtkitError;
+
+    fn try_from(policy: SchedPolicy) -> Result<RealtimeThreadSchedulePolicy, RtkitError> {
+        match policy {
+            SchedPolicy::Fifo => Ok(RealtimeThreadSchedulePolicy::Fifo),
+            SchedPolicy::Rr => Ok(RealtimeThreadSchedulePolicy::RoundRobin),
+            SchedPolicy::Deadline => Ok(RealtimeThreadSchedulePolicy::Deadline),
+            policy => Err(RtkitError::ThreadPriorityNotConvertible(format!(
+                "{policy:?}"
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_priority_conversion() {
+        let priority = ThreadPriority::Crossplatform(10u8.try_into().unwrap());
+        assert_eq!(RealtimePriority::try_from(priority).unwrap().get(), 10);
+        assert_eq!(
+            ThreadPriority::try_from(RealtimePriority::from(10)).unwrap(),
+            priority
+        );
+
+        assert!(matches!(
+            RealtimePriority::try_from(ThreadPriority::Max),
+            Err(RtkitError::ThreadPriorityNotConvertible(_))
+        ));
+        assert!(ThreadPriority::try_from(RealtimePriority::from(100)).is_err());
+    }
+
+    #[test]
+    fn test_policy_conversion() {
+        for policy in [SchedPolicy::Fifo, SchedPolicy::Rr, SchedPolicy::Deadline] {
+            let converted = RealtimeThreadSchedulePolicy::try_from(policy).unwrap();
+            assert_eq!(SchedPolicy::from(converted), policy);
+        }
+
+        assert!(matches!(
+            RealtimeThreadSchedulePolicy::try_from(SchedPolicy::Other),
+            Err(RtkitError::ThreadPriorityNotConvertible(_))
+        ));
+    }
+}